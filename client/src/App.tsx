@@ -8,6 +8,6 @@ export const App = () => {
   const [state, dispatch] = React.useReducer(appReducer, initialAppState)
 
   return state.websocket === null
-    ? <LoginForm dispatch={dispatch} />
+    ? <LoginForm loginError={state.loginError} dispatch={dispatch} />
     : <GameComponent state={state} dispatch={dispatch} />
 }
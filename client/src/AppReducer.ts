@@ -1,10 +1,12 @@
-import { Line, PlayerUpdate, RoomInfo } from "./PlayerUpdate";
+import { Line, MapInfo, PlayerUpdate, RoomInfo } from "./PlayerUpdate";
 
 export interface AppState {
   websocket: WebSocket | null;
   lines: ReadonlyArray<StoredLine>;
   nextLineId: number;
   roomInfo: RoomInfo | null;
+  mapInfo: MapInfo | null;
+  loginError: string | null;
 }
 
 export interface StoredLine {
@@ -18,6 +20,8 @@ export const initialAppState: AppState = {
   lines: [],
   nextLineId: 0,
   roomInfo: null,
+  mapInfo: null,
+  loginError: null,
 };
 
 export type AppAction =
@@ -37,7 +41,12 @@ export function appReducer(state: AppState, action: AppAction): AppState {
 
     case "websocketClosed":
       console.warn("Websocket closed");
-      return initialAppState;
+      return {
+        ...initialAppState,
+        loginError: state.websocket === null && action.event instanceof CloseEvent
+          ? action.event.reason || null
+          : null,
+      };
 
     case "websocketMessage": {
       try {
@@ -68,6 +77,9 @@ function applyPlayerUpdate(
   if (playerUpdate.roomInfo) {
     state = { ...state, roomInfo: playerUpdate.roomInfo };
   }
+  if (playerUpdate.mapInfo) {
+    state = { ...state, mapInfo: playerUpdate.mapInfo };
+  }
   return state;
 }
 
@@ -1,6 +1,7 @@
 import * as React from "react";
 
 import { AppDispatch, AppState, StoredLine } from "./AppReducer";
+import { Minimap } from "./Minimap";
 import { EntityInfo } from "./PlayerUpdate";
 import { sendCommand } from "./ServerConnection";
 
@@ -10,7 +11,7 @@ export interface Props {
 }
 
 export const GameComponent = ({ state, dispatch }: Props) => {
-  const { websocket, lines, roomInfo } = state;
+  const { websocket, lines, roomInfo, mapInfo } = state;
 
   const refLogContainer = React.useRef<HTMLDivElement>(null);
   React.useEffect(() => {
@@ -57,6 +58,7 @@ export const GameComponent = ({ state, dispatch }: Props) => {
         </div>
       </div>
       <div className="sidebar">
+        {mapInfo && <Minimap mapInfo={mapInfo} />}
         {roomInfo && (
           <div className="roomEntities">
             <RoomEntityRow entity={roomInfo.selfPlayer} nameClass="white" />
@@ -109,17 +111,48 @@ const LineComponent = (props: StoredLine) => {
   );
 };
 
-const RoomEntityRow = (props: { entity: EntityInfo; nameClass: string }) => (
-  <div>
-    <div className={`name ${props.nameClass}`}>{props.entity.name}</div>
-    <Gauge filled={props.entity.hp} total={props.entity.maxHp} />
-  </div>
-);
+const effectIcons: Record<string, string> = {
+  poison: "P",
+  regeneration: "R",
+  stun: "S",
+  haste: "H",
+  blessing: "B",
+};
+
+const RoomEntityRow = (props: { entity: EntityInfo; nameClass: string }) => {
+  const { entity } = props;
+  return (
+    <div>
+      <div className={`name ${props.nameClass}`}>
+        {entity.name}
+        {entity.effects?.map((effect) => (
+          <span key={effect} className={`effect ${effect}`} title={effect}>
+            {effectIcons[effect] ?? "?"}
+          </span>
+        ))}
+      </div>
+      <div className="gauges">
+        <Gauge filled={entity.hp} total={entity.maxHp} />
+        {entity.mana !== undefined && entity.maxMana !== undefined && (
+          <Gauge
+            filled={entity.mana}
+            total={entity.maxMana}
+            className="mana"
+          />
+        )}
+      </div>
+    </div>
+  );
+};
 
-const Gauge = (props: { filled: number; total: number }) => {
-  const { filled, total } = props;
+const Gauge = (props: {
+  filled: number;
+  total: number;
+  className?: string;
+}) => {
+  const { filled, total, className } = props;
   return (
-    <div className="gauge">
+    <div className={className ? `gauge ${className}` : "gauge"}>
       <div className="filled" style={{ width: `${(100 * filled) / total}%` }} />
       <div>
         {filled}/{total}
@@ -3,19 +3,21 @@ import { AppDispatch } from "./AppReducer"
 import { connectToServer } from "./ServerConnection"
 
 export interface Props {
+  loginError: string | null
   dispatch: AppDispatch
 }
 
-export const LoginForm = ({ dispatch }: Props) => {
+export const LoginForm = ({ loginError, dispatch }: Props) => {
   const [name, setName] = React.useState("")
+  const [password, setPassword] = React.useState("")
 
   const loginClicked = React.useCallback(e => {
     e.preventDefault()
     const trimmedName = name.trim()
     if (trimmedName !== "") {
-      connectToServer(trimmedName, dispatch)
+      connectToServer(trimmedName, password, dispatch)
     }
-  }, [name])
+  }, [name, password])
 
   return <div className="loginForm">
     <form onSubmit={loginClicked}>
@@ -23,6 +25,11 @@ export const LoginForm = ({ dispatch }: Props) => {
         <label>Name:</label>
         <input value={name} onChange={e => setName(e.target.value)} autoFocus />
       </div>
+      <div>
+        <label>Password:</label>
+        <input type="password" value={password} onChange={e => setPassword(e.target.value)} />
+      </div>
+      {loginError !== null && <div className="loginError">{loginError}</div>}
       <button onClick={loginClicked}>Log in</button>
     </form>
   </div>
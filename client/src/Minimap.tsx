@@ -0,0 +1,96 @@
+import * as React from "react";
+
+import { MapExit, MapInfo } from "./PlayerUpdate";
+
+const radius = 2;
+const roomSize = 0.5;
+
+const compassOffsets: { [direction: string]: [number, number] } = {
+  north: [0, -1],
+  northeast: [1, -1],
+  east: [1, 0],
+  southeast: [1, 1],
+  south: [0, 1],
+  southwest: [-1, 1],
+  west: [-1, 0],
+  northwest: [-1, -1],
+};
+
+interface Props {
+  mapInfo: MapInfo;
+}
+
+export const Minimap = ({ mapInfo }: Props) => {
+  const rooms = [
+    {
+      id: mapInfo.roomId,
+      name: mapInfo.name,
+      x: 0,
+      y: 0,
+      exits: mapInfo.exits,
+    },
+    ...mapInfo.neighbours,
+  ];
+  const positions = new Map(
+    rooms.map((room): [string, number[]] => [room.id, [room.x, room.y]])
+  );
+
+  const exitLines = rooms.flatMap((room) =>
+    room.exits.flatMap((exit) => {
+      const to = exitEnd(exit, room.x, room.y, positions);
+      return to === null
+        ? []
+        : [
+            <line
+              key={`${room.id}-${exit.direction}`}
+              x1={room.x}
+              y1={room.y}
+              x2={to[0]}
+              y2={to[1]}
+              className={exit.open ? "exit" : "exit closed"}
+            />,
+          ];
+    })
+  );
+
+  const viewSize = 2 * radius + 1;
+  return (
+    <div>
+      <div className="minimapName white">{mapInfo.name}</div>
+      <svg
+        className="minimap"
+        width="100%"
+        viewBox={`${-radius - 0.5} ${-radius - 0.5} ${viewSize} ${viewSize}`}
+      >
+        {exitLines}
+        {rooms.map((room) => (
+          <rect
+            key={room.id}
+            x={room.x - roomSize / 2}
+            y={room.y - roomSize / 2}
+            width={roomSize}
+            height={roomSize}
+            className={room.id === mapInfo.roomId ? "room current" : "room"}
+          >
+            <title>{room.name}</title>
+          </rect>
+        ))}
+      </svg>
+    </div>
+  );
+};
+
+// Exits to rooms not on the map are drawn as stubs in their compass direction.
+function exitEnd(
+  exit: MapExit,
+  x: number,
+  y: number,
+  positions: Map<string, number[]>
+): number[] | null {
+  const to = positions.get(exit.toRoomId);
+  if (to !== undefined) {
+    return to;
+  }
+  const offset = compassOffsets[exit.direction];
+  return offset === undefined ? null : [x + offset[0] / 2, y + offset[1] / 2];
+}
@@ -1,6 +1,7 @@
 export interface PlayerUpdate {
   lines: ReadonlyArray<Line>;
   roomInfo?: RoomInfo;
+  mapInfo?: MapInfo;
 }
 
 export interface Line {
@@ -19,9 +20,33 @@ export interface RoomInfo {
   mobs: EntityInfo[];
 }
 
+export interface MapInfo {
+  roomId: string;
+  name: string;
+  exits: MapExit[];
+  neighbours: MapRoom[];
+}
+
+export interface MapExit {
+  direction: string;
+  toRoomId: string;
+  open: boolean;
+}
+
+export interface MapRoom {
+  id: string;
+  name: string;
+  x: number;
+  y: number;
+  exits: MapExit[];
+}
+
 export interface EntityInfo {
   id: string;
   name: string;
   hp: number;
   maxHp: number;
+  mana?: number;
+  maxMana?: number;
+  effects?: string[];
 }
@@ -1,10 +1,19 @@
 import { AppDispatch } from "./AppReducer"
 
-export function connectToServer(playerName: string, dispatch: AppDispatch): void {
+export function connectToServer(playerName: string, password: string, dispatch: AppDispatch): void {
   const schema = window.location.hostname === "localhost" ? "ws" : "wss"
   const ws = new WebSocket(`${schema}://${window.location.host}/api/ws?name=${decodeURIComponent(playerName)}`)
-  ws.onopen = () => dispatch({ type: "websocketConnected", websocket: ws })
-  ws.onmessage = (e) => dispatch({ type: "websocketMessage", message: e.data })
+  // The server expects the password as the first message, and closes the connection with the
+  // reason if it refuses the login.
+  let loggedIn = false
+  ws.onopen = () => ws.send(password)
+  ws.onmessage = (e) => {
+    if (!loggedIn) {
+      loggedIn = true
+      dispatch({ type: "websocketConnected", websocket: ws })
+    }
+    dispatch({ type: "websocketMessage", message: e.data })
+  }
   ws.onclose = (e) => dispatch({ type: "websocketClosed", event: e, isError: false })
   ws.onerror = (e) => dispatch({ type: "websocketClosed", event: e, isError: true })
 }
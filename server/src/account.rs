@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    Player,
+    Builder,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Player => "player",
+            Role::Builder => "builder",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub name: String,
+    pub role: Role,
+    /// An argon2 hash, as printed by `mud hash-password <password>`. Accounts without one cannot
+    /// be logged into.
+    #[serde(default)]
+    pub password_hash: Option<String>,
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use log::warn;
+
+use crate::{account::Account, character::SavedCharacter};
+
+/// Checks who may log in as whom, before a connection reaches the game.
+pub struct Authenticator {
+    accounts: HashMap<String, Account>,
+    online_names: Mutex<HashSet<String>>,
+}
+
+impl Authenticator {
+    pub fn new(accounts: &[Account]) -> Authenticator {
+        Authenticator {
+            accounts: accounts
+                .iter()
+                .map(|account| (account.name.to_ascii_lowercase(), account.clone()))
+                .collect(),
+            online_names: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Verifies the password against the account by that name, or else against the character's
+    /// save. Characters that have never been saved, or were saved before they had passwords, are
+    /// claimed with the given password.
+    pub async fn authenticate(
+        &self,
+        name: &str,
+        password: String,
+        saved: Option<SavedCharacter>,
+    ) -> Result<SavedCharacter, &'static str> {
+        if let Some(account) = self.accounts.get(&name.to_ascii_lowercase()) {
+            let Some(hash) = account.password_hash.clone() else {
+                warn!(
+                    "Account {} has no password hash and cannot log in",
+                    account.name
+                );
+                return Err("That account cannot be logged into.");
+            };
+            return if verify_password(password, hash).await {
+                Ok(saved.unwrap_or_default())
+            } else {
+                Err("Wrong password.")
+            };
+        }
+
+        let mut saved = saved.unwrap_or_default();
+        if let Some(hash) = saved.password_hash.clone() {
+            return if verify_password(password, hash).await {
+                Ok(saved)
+            } else {
+                Err("Wrong password.")
+            };
+        }
+        if password.is_empty() {
+            return Err("Choose a password for your new character.");
+        }
+        let hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|result| result)
+            .map_err(|err| {
+                warn!("Failed to hash a password: {}", err);
+                "Your character could not be created."
+            })?;
+        saved.password_hash = Some(hash);
+        Ok(saved)
+    }
+
+    /// Marks the name as online, unless it already is.
+    pub fn claim_name(&self, name: &str) -> bool {
+        self.online_names.lock().unwrap().insert(name.to_ascii_lowercase())
+    }
+
+    pub fn release_name(&self, name: &str) {
+        self.online_names.lock().unwrap().remove(&name.to_ascii_lowercase());
+    }
+}
+
+/// Hashes a password for storing in `data/accounts.yaml` or a character's save.
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| err.to_string())
+}
+
+async fn verify_password(password: String, hash: String) -> bool {
+    // Hashing is deliberately slow, so keep it off the async workers.
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&hash)
+            .map_err(|err| warn!("Invalid password hash: {}", err))
+            .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::account::Role;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn new_characters_are_claimed_on_first_login() {
+        let authenticator = Authenticator::new(&[]);
+        let saved = authenticator.authenticate("Ann", "hunter2".to_string(), None).await.unwrap();
+        assert!(saved.password_hash.is_some());
+
+        let again = authenticator.authenticate("Ann", "hunter2".to_string(), Some(saved)).await;
+        assert!(again.is_ok());
+    }
+
+    #[tokio::test]
+    async fn new_characters_need_a_password() {
+        let authenticator = Authenticator::new(&[]);
+        assert!(authenticator.authenticate("Ann", String::new(), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn wrong_passwords_are_refused() {
+        let authenticator = Authenticator::new(&[Account {
+            name: "Admin".to_string(),
+            role: Role::Admin,
+            password_hash: Some(hash_password("secret").unwrap()),
+        }]);
+        assert!(authenticator.authenticate("admin", "secret".to_string(), None).await.is_ok());
+        assert!(authenticator.authenticate("admin", "guess".to_string(), None).await.is_err());
+
+        let saved = SavedCharacter {
+            password_hash: Some(hash_password("hunter2").unwrap()),
+            ..SavedCharacter::default()
+        };
+        let result = authenticator.authenticate("Ann", "guess".to_string(), Some(saved)).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn accounts_without_a_hash_cannot_log_in() {
+        let authenticator = Authenticator::new(&[Account {
+            name: "Builder".to_string(),
+            role: Role::Builder,
+            password_hash: None,
+        }]);
+        assert!(authenticator.authenticate("builder", "".to_string(), None).await.is_err());
+    }
+
+    #[test]
+    fn names_can_only_be_online_once() {
+        let authenticator = Authenticator::new(&[]);
+        assert!(authenticator.claim_name("Ann"));
+        assert!(!authenticator.claim_name("ann"));
+        authenticator.release_name("ANN");
+        assert!(authenticator.claim_name("Ann"));
+    }
+}
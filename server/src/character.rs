@@ -0,0 +1,112 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    event_writer::EventWriter,
+    file_io::{self, FileIo},
+    game_state::Room,
+    id::Id,
+    player::Player,
+};
+
+static SAVES_DIR: &str = "saves";
+static MAX_NAME_LENGTH: usize = 20;
+
+/// The part of a player that outlives their connection.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedCharacter {
+    /// An argon2 hash of the password chosen when the character was created.
+    #[serde(default)]
+    pub password_hash: Option<String>,
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// Lowercase names of the players whose chat is hidden.
+    #[serde(default)]
+    pub ignored: BTreeSet<String>,
+    #[serde(default)]
+    pub visited_rooms: Vec<Id<Room>>,
+    #[serde(default)]
+    pub skills: BTreeSet<String>,
+    #[serde(default)]
+    pub wimpy: Option<i32>,
+    #[serde(default)]
+    pub pvp: bool,
+    #[serde(default)]
+    pub player_kills: u32,
+}
+
+impl SavedCharacter {
+    pub fn from_player(player: &Player) -> SavedCharacter {
+        let mut visited_rooms = player.visited_rooms.iter().copied().collect::<Vec<_>>();
+        visited_rooms.sort_by_key(|room_id| room_id.value);
+        SavedCharacter {
+            password_hash: player.password_hash.clone(),
+            aliases: player.aliases.clone(),
+            ignored: player.ignored.iter().cloned().collect(),
+            visited_rooms,
+            skills: player.skills.clone(),
+            wimpy: player.wimpy,
+            pvp: player.pvp,
+            player_kills: player.player_kills,
+        }
+    }
+
+    /// Loads the character's save, or None if the character has never been saved.
+    pub async fn load(
+        player_name: &str,
+        file_io: &FileIo,
+    ) -> Result<Option<SavedCharacter>, String> {
+        let path = save_path(player_name).ok_or("load: Invalid character name")?;
+        match file_io::read(file_io, path.clone()).await {
+            Some(yaml) => serde_yaml::from_str(&yaml)
+                .map(Some)
+                .map_err(|err| format!("Failed to parse {}: {}", path.display(), err)),
+            None => Ok(None),
+        }
+    }
+}
+
+pub fn save_player(player: &Player, writer: &mut EventWriter) {
+    let Some(path) = save_path(&player.name) else {
+        warn!("save: Invalid character name {}", player.name);
+        return;
+    };
+    match serde_yaml::to_string(&SavedCharacter::from_player(player)) {
+        Ok(yaml) => writer.write_file(path, yaml),
+        Err(err) => warn!("save {}: {}", path.display(), err),
+    }
+}
+
+/// Whether the name can be played with. Names are used as file names as they are, so only
+/// letters, digits, `-` and `_` are allowed.
+pub fn is_valid_name(player_name: &str) -> bool {
+    !player_name.is_empty()
+        && player_name.len() <= MAX_NAME_LENGTH
+        && player_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn save_path(player_name: &str) -> Option<PathBuf> {
+    is_valid_name(player_name).then(|| {
+        PathBuf::from(SAVES_DIR).join(format!("{}.yaml", player_name.to_ascii_lowercase()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_must_be_safe_file_names() {
+        assert!(is_valid_name("Ann_the-2nd"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../ann"));
+        assert!(!is_valid_name("Ann Smith"));
+        assert!(!is_valid_name(&"a".repeat(MAX_NAME_LENGTH + 1)));
+    }
+}
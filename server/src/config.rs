@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub commands_per_tick: usize,
+    pub max_queued_commands: usize,
+    /// Chance per second that a zone with ambient messages shows one to its players.
+    pub ambient_message_chance: f64,
+    /// How many real seconds an hour of the in-game day lasts.
+    pub game_hour_secs: f32,
+    /// Seeds the random numbers of combat, so that fights play out the same on every run.
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
+}
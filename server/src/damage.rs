@@ -0,0 +1,164 @@
+use std::fmt;
+
+use rand::Rng;
+use serde::Deserialize;
+
+/// The roll an attack needs to reach, after adding the attacker's accuracy and subtracting the
+/// defender's defense, on a d20.
+static HIT_THRESHOLD: i32 = 6;
+static CRITICAL_MULTIPLIER: i32 = 2;
+static RESISTANCE_MULTIPLIER: f64 = 0.5;
+static VULNERABILITY_MULTIPLIER: f64 = 2.0;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DamageType {
+    #[default]
+    Bludgeoning,
+    Slashing,
+    Piercing,
+    Fire,
+    Cold,
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How a target takes damage of some types, as set on mob templates.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DamageModifiers {
+    /// Types the target takes half damage from.
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    /// Types the target takes double damage from.
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+}
+
+impl DamageModifiers {
+    pub fn apply(&self, damage: i32, damage_type: DamageType) -> i32 {
+        let multiplier = if self.vulnerabilities.contains(&damage_type) {
+            VULNERABILITY_MULTIPLIER
+        } else if self.resistances.contains(&damage_type) {
+            RESISTANCE_MULTIPLIER
+        } else {
+            1.0
+        };
+        ((f64::from(damage) * multiplier).round() as i32).max(1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitOutcome {
+    Miss,
+    Hit,
+    Critical,
+}
+
+/// Rolls a d20 for an attack. A natural 20 is always a critical hit and a natural 1 always
+/// misses.
+pub fn roll_attack(accuracy: i32, defense: i32, rng: &mut impl Rng) -> HitOutcome {
+    match rng.gen_range(1..=20) {
+        20 => HitOutcome::Critical,
+        1 => HitOutcome::Miss,
+        roll if roll + accuracy - defense >= HIT_THRESHOLD => HitOutcome::Hit,
+        _ => HitOutcome::Miss,
+    }
+}
+
+/// The damage an attack deals on average, counting misses and critical hits.
+pub fn expected_damage(base: i32, accuracy: i32, defense: i32) -> f64 {
+    let hitting_rolls = (2..20).filter(|roll| roll + accuracy - defense >= HIT_THRESHOLD).count();
+    // The natural 20 counts as many hits as a critical multiplies.
+    let expected_hits = hitting_rolls as f64 + f64::from(CRITICAL_MULTIPLIER);
+    f64::from(base) * expected_hits / 20.0
+}
+
+/// Rolls the damage of a landed hit, within a quarter of the base damage either way.
+pub fn roll_damage(base: i32, outcome: HitOutcome, rng: &mut impl Rng) -> i32 {
+    let spread = base / 4;
+    let damage = (base + rng.gen_range(-spread..=spread)).max(1);
+    if outcome == HitOutcome::Critical {
+        damage * CRITICAL_MULTIPLIER
+    } else {
+        damage
+    }
+}
+
+/// The verb describing a hit, in second and third person, by how big a share of the target's
+/// max HP it takes away.
+pub fn hit_verbs(damage: i32, max_hp: i32) -> (&'static str, &'static str) {
+    let share = f64::from(damage) / f64::from(max_hp.max(1));
+    if share < 0.05 {
+        ("barely scratch", "barely scratches")
+    } else if share < 0.12 {
+        ("hit", "hits")
+    } else if share < 0.25 {
+        ("wound", "wounds")
+    } else if share < 0.4 {
+        ("maul", "mauls")
+    } else {
+        ("obliterate", "obliterates")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn expected_damage_matches_the_rolls() {
+        assert_eq!(expected_damage(10, 0, 0), 8.0);
+
+        let mut rng = StdRng::seed_from_u64(47);
+        let attacks = 100_000;
+        let total = (0..attacks)
+            .map(|_| match roll_attack(2, 5, &mut rng) {
+                HitOutcome::Miss => 0,
+                outcome => roll_damage(12, outcome, &mut rng),
+            })
+            .sum::<i32>();
+        let average = f64::from(total) / f64::from(attacks);
+        let expected = expected_damage(12, 2, 5);
+        assert!(
+            (average - expected).abs() < expected * 0.02,
+            "{} vs {}",
+            average,
+            expected
+        );
+    }
+
+    #[test]
+    fn critical_hits_double_the_damage() {
+        for seed in 0..20 {
+            let hit = roll_damage(10, HitOutcome::Hit, &mut StdRng::seed_from_u64(seed));
+            let critical = roll_damage(10, HitOutcome::Critical, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(critical, hit * CRITICAL_MULTIPLIER);
+        }
+    }
+
+    #[test]
+    fn resistances_and_vulnerabilities_scale_damage() {
+        let modifiers = DamageModifiers {
+            resistances: vec![DamageType::Fire],
+            vulnerabilities: vec![DamageType::Cold],
+        };
+        assert_eq!(modifiers.apply(10, DamageType::Fire), 5);
+        assert_eq!(modifiers.apply(10, DamageType::Cold), 20);
+        assert_eq!(modifiers.apply(10, DamageType::Slashing), 10);
+        assert_eq!(modifiers.apply(1, DamageType::Fire), 1);
+    }
+}
@@ -1,12 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
-use crate::{id::Id, line::Line, player::Player};
+use crate::{file_io::FileRequest, id::Id, line::Line, player::Player};
 
 pub struct EventWriter {
     pub lines: HashMap<Id<Player>, Vec<Line>>,
+    pub disconnects: HashSet<Id<Player>>,
+    pub audit_entries: Vec<String>,
+    pub file_requests: Vec<FileRequest>,
 }
 
 impl EventWriter {
+    pub fn new() -> EventWriter {
+        EventWriter {
+            lines: HashMap::new(),
+            disconnects: HashSet::new(),
+            audit_entries: Vec::new(),
+            file_requests: Vec::new(),
+        }
+    }
+
     pub fn tell(&mut self, player_id: Id<Player>, line: Line) {
         if let Some(existing) = self.lines.get_mut(&player_id) {
             existing.push(line);
@@ -29,4 +44,18 @@ impl EventWriter {
             self.tell(player_id, line.clone());
         }
     }
+
+    /// Closes the player's connection after the pending lines have been sent.
+    pub fn disconnect(&mut self, player_id: Id<Player>) {
+        self.disconnects.insert(player_id);
+    }
+
+    pub fn audit(&mut self, entry: String) {
+        self.audit_entries.push(entry);
+    }
+
+    /// Replaces the file's contents once the current message has been handled.
+    pub fn write_file(&mut self, path: impl Into<PathBuf>, contents: String) {
+        self.file_requests.push(FileRequest::Replace { path: path.into(), contents });
+    }
 }
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+    sync::{mpsc, oneshot},
+};
+
+/// Sends requests to the file task. Requests are performed one after another in the order they
+/// are sent, so a read always sees the writes sent before it.
+pub type FileIo = mpsc::UnboundedSender<FileRequest>;
+
+/// Disk access, done on a background task so the game never waits for it.
+#[derive(Debug)]
+pub enum FileRequest {
+    Append {
+        path: PathBuf,
+        contents: String,
+    },
+    /// Replaces the whole file, through a temporary file so a crash never leaves it half written.
+    Replace {
+        path: PathBuf,
+        contents: String,
+    },
+    /// Reads the whole file, replying with None if it does not exist or cannot be read.
+    Read {
+        path: PathBuf,
+        reply: oneshot::Sender<Option<String>>,
+    },
+}
+
+impl FileRequest {
+    fn path(&self) -> &Path {
+        match self {
+            FileRequest::Append { path, .. }
+            | FileRequest::Replace { path, .. }
+            | FileRequest::Read { path, .. } => path,
+        }
+    }
+}
+
+/// Starts the task that performs file requests.
+pub fn spawn() -> FileIo {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<FileRequest>();
+    tokio::spawn(async move {
+        while let Some(request) = receiver.recv().await {
+            let path = request.path().to_path_buf();
+            if let Err(err) = perform(request).await {
+                warn!("Failed to write {}: {}", path.display(), err);
+            }
+        }
+    });
+    sender
+}
+
+pub fn send(file_io: &FileIo, request: FileRequest) {
+    if file_io.send(request).is_err() {
+        warn!("File request dropped: the file task has stopped");
+    }
+}
+
+pub async fn read(file_io: &FileIo, path: PathBuf) -> Option<String> {
+    let (reply, response) = oneshot::channel();
+    send(file_io, FileRequest::Read { path, reply });
+    response.await.ok().flatten()
+}
+
+async fn perform(request: FileRequest) -> std::io::Result<()> {
+    match request {
+        FileRequest::Append { path, contents } => {
+            let mut file = OpenOptions::new().create(true).append(true).open(path).await?;
+            file.write_all(contents.as_bytes()).await
+        }
+        FileRequest::Replace { path, contents } => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            let temp_path = path.with_extension("tmp");
+            fs::write(&temp_path, contents).await?;
+            fs::rename(&temp_path, path).await
+        }
+        FileRequest::Read { path, reply } => {
+            let _ = reply.send(fs::read_to_string(path).await.ok());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use once_cell::sync::Lazy;
+
+use crate::{
+    event_writer::EventWriter,
+    game_combat, game_spawn,
+    game_state::GameState,
+    id::Id,
+    line::{span, Color, Line},
+    mob::{AbilityEffect, Mob},
+    tick::TickDuration,
+};
+
+pub static TELEGRAPH_DELAY: Lazy<TickDuration> = Lazy::new(|| TickDuration::from_secs(3.0));
+
+/// Lets fighting mobs use their special abilities as they come off cooldown. An ability with a
+/// telegraph is announced first and goes off after a short delay.
+pub fn tick_mob_abilities(writer: &mut EventWriter, state: &mut GameState) {
+    dismiss_summons(writer, state);
+
+    let mob_ids = state
+        .mobs
+        .by_id()
+        .values()
+        .filter(|mob| !mob.template.abilities.is_empty())
+        .map(|mob| mob.id)
+        .collect::<Vec<_>>();
+
+    for mob_id in mob_ids {
+        let Some(mob) = state.mobs.by_id().get(&mob_id) else {
+            continue;
+        };
+        let now = state.ticks;
+        if mob.is_stunned(now) {
+            continue;
+        }
+
+        if !is_fighting(mob, state) {
+            if !mob.ability_ready_at.is_empty() || mob.pending_ability.is_some() {
+                state.mobs.modify(&mob_id, |mob| {
+                    mob.ability_ready_at.clear();
+                    mob.pending_ability = None;
+                });
+            }
+            continue;
+        }
+
+        match mob.pending_ability {
+            Some((index, goes_off_at)) if goes_off_at <= now => {
+                state.mobs.modify(&mob_id, |mob| mob.pending_ability = None);
+                use_ability(mob_id, index, writer, state);
+            }
+            Some(_) => (),
+            None => {
+                let ready = mob.template.abilities.iter().enumerate().find(|(index, ability)| {
+                    mob.ability_ready_at.get(index).is_some_and(|ready_at| *ready_at <= now)
+                        && mob.can_use(ability)
+                });
+                let ready = ready.map(|(index, ability)| (index, ability.telegraph.clone()));
+                let room_id = mob.room_id;
+
+                state.mobs.modify(&mob_id, |mob| {
+                    for (index, ability) in mob.template.abilities.iter().enumerate() {
+                        mob.ability_ready_at.entry(index).or_insert(now + ability.cooldown);
+                    }
+                });
+                match ready {
+                    Some((index, Some(telegraph))) => {
+                        writer.tell_many(
+                            state.players.ids_in_room(room_id),
+                            span(&telegraph).color(Color::Yellow).bold().line(),
+                        );
+                        state.mobs.modify(&mob_id, |mob| {
+                            mob.pending_ability = Some((index, now + *TELEGRAPH_DELAY))
+                        });
+                    }
+                    Some((index, None)) => use_ability(mob_id, index, writer, state),
+                    None => (),
+                }
+            }
+        }
+    }
+}
+
+/// Removes summoned mobs whose summoner has died or is no longer in the fight.
+fn dismiss_summons(writer: &mut EventWriter, state: &mut GameState) {
+    let dismissed = state
+        .mobs
+        .by_id()
+        .values()
+        .filter(|mob| {
+            mob.summoner.is_some_and(|summoner_id| {
+                !state.mobs.by_id().get(&summoner_id).is_some_and(|summoner| {
+                    is_fighting(summoner, state) || is_threatened(summoner, state)
+                })
+            })
+        })
+        .map(|mob| (mob.id, mob.room_id, mob.template.name.clone()))
+        .collect::<Vec<_>>();
+    for (mob_id, room_id, mob_name) in dismissed {
+        writer.tell_many(
+            state.players.ids_in_room(room_id),
+            Line::str(&format!("The {} vanishes.", mob_name)),
+        );
+        game_combat::remove_mob(mob_id, state);
+    }
+}
+
+/// Whether a player the mob is hostile to is in its room, which keeps it in the fight between
+/// targets.
+fn is_threatened(mob: &Mob, state: &GameState) -> bool {
+    state
+        .players
+        .ids_in_room(mob.room_id)
+        .any(|player_id| mob.hostile_to.contains(&player_id))
+}
+
+fn is_fighting(mob: &Mob, state: &GameState) -> bool {
+    mob.attack_target
+        .and_then(|target_id| state.players.by_id().get(&target_id))
+        .is_some_and(|target| target.room_id == mob.room_id)
+}
+
+fn use_ability(mob_id: Id<Mob>, index: usize, writer: &mut EventWriter, state: &mut GameState) {
+    let Some(mob) = state.mobs.by_id().get(&mob_id) else {
+        return;
+    };
+    let Some(ability) = mob.template.abilities.get(index).cloned() else {
+        return;
+    };
+    let room_id = mob.room_id;
+    let attack_target = mob.attack_target;
+    let hostile_to = mob.hostile_to.clone();
+
+    writer.tell_many(
+        state.players.ids_in_room(room_id),
+        span(&ability.message).color(Color::LightRed).line(),
+    );
+    let ready_at = state.ticks + ability.cooldown;
+    state.mobs.modify(&mob_id, |mob| mob.ability_ready_at.insert(index, ready_at));
+
+    match ability.effect {
+        AbilityEffect::Breath { damage } => {
+            let target_ids = state
+                .players
+                .ids_in_room(room_id)
+                .filter(|player_id| hostile_to.contains(player_id))
+                .collect::<Vec<_>>();
+            for target_id in target_ids {
+                let msg_target = format!("You are hit for {} damage.", damage);
+                writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+                if game_combat::damage_player(target_id, damage, &mut state.players, writer) {
+                    game_combat::respawn_player(target_id, writer, state);
+                }
+            }
+        }
+        AbilityEffect::Afflict(application) => {
+            let Some(target_id) = attack_target else {
+                return;
+            };
+            let now = state.ticks;
+            let target_name = state.players.modify(&target_id, |target| {
+                target.status_effects.apply(&application, now);
+                target.name.clone()
+            });
+            let adjective = application.status.adjective();
+            let msg_target = format!("You are {}!", adjective);
+            writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+            let msg_others = format!("{} is {}.", target_name, adjective);
+            writer.tell_many(
+                state.players.ids_in_room_except(room_id, target_id),
+                span(&msg_others).color(Color::Red).line(),
+            );
+        }
+        AbilityEffect::Empower(application) => {
+            let now = state.ticks;
+            state.mobs.modify(&mob_id, |mob| mob.status_effects.apply(&application, now));
+        }
+        AbilityEffect::Summon { template, count } => {
+            let Some(template) = state.mob_templates.get(&template).cloned() else {
+                return;
+            };
+            let live_summons =
+                state.mobs.by_id().values().filter(|mob| mob.summoner == Some(mob_id)).count();
+            for _ in live_summons..count {
+                let summoned_id = game_spawn::spawn_mob(room_id, template.clone(), None, state);
+                state.mobs.modify(&summoned_id, |summoned| {
+                    summoned.summoner = Some(mob_id);
+                    summoned.hostile_to = hostile_to.clone();
+                });
+                writer.tell_many(
+                    state.players.ids_in_room(room_id),
+                    Line::str(&format!("A {} appears.", template.name)),
+                );
+            }
+        }
+        AbilityEffect::Enrage { multiplier, .. } => {
+            state.mobs.modify(&mob_id, |mob| {
+                mob.enraged = true;
+                mob.damage = (f64::from(mob.damage) * multiplier).round() as i32;
+            });
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{
+    event_writer::EventWriter,
+    game_combat,
+    game_logic::on_player_disconnect,
+    game_room::{arrive_in_room, resolve_targets_in_room, RoomTarget},
+    game_spawn::spawn_mob,
+    game_state::{GameState, Room},
+    id::Id,
+    line::{span, Color, Line},
+    named::Named,
+    player::Player,
+};
+
+pub fn goto(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let room_str = words.join(" ");
+    match find_room(&room_str, state) {
+        Some(room_id) => {
+            teleport(player_id, room_id, writer, state)?;
+            audit(player_id, &format!("goto {}", room_id.value), writer, state);
+        }
+        None => writer.tell(player_id, Line::str("There is no such room.")),
+    }
+    Ok(())
+}
+
+pub fn summon(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ");
+    let room_id = state.players.by_id().get(&player_id).ok_or("summon: Self not found")?.room_id;
+    match state.players.by_name(&name) {
+        Some(target) if target.id == player_id => {
+            writer.tell(player_id, Line::str("You are already here."));
+        }
+        Some(target) => {
+            let target_id = target.id;
+            writer.tell(target_id, Line::str("You feel a strong pull..."));
+            teleport(target_id, room_id, writer, state)?;
+            audit(player_id, &format!("summon {}", name), writer, state);
+        }
+        None => writer.tell(player_id, Line::str("There is no such player online.")),
+    }
+    Ok(())
+}
+
+pub fn kick(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ");
+    match state.players.by_name(&name) {
+        Some(target) => {
+            let target_id = target.id;
+            writer.tell(
+                target_id,
+                span("You have been kicked.").color(Color::LightRed).line(),
+            );
+            writer.tell(player_id, Line::str(&format!("You kick {}.", name)));
+            writer.disconnect(target_id);
+            on_player_disconnect(target_id, writer, state);
+            audit(player_id, &format!("kick {}", name), writer, state);
+        }
+        None => writer.tell(player_id, Line::str("There is no such player online.")),
+    }
+    Ok(())
+}
+
+pub fn ban(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ");
+    if name.eq_ignore_ascii_case(&state.players.by_id().get(&player_id).ok_or("ban")?.name) {
+        writer.tell(player_id, Line::str("You cannot ban yourself."));
+        return Ok(());
+    }
+    state.banned.insert(name.to_ascii_lowercase());
+    writer.tell(player_id, Line::str(&format!("You ban {}.", name)));
+    if let Some(target) = state.players.by_name(&name) {
+        let target_id = target.id;
+        writer.tell(
+            target_id,
+            span("You have been banned.").color(Color::LightRed).line(),
+        );
+        writer.disconnect(target_id);
+        on_player_disconnect(target_id, writer, state);
+    }
+    audit(player_id, &format!("ban {}", name), writer, state);
+    Ok(())
+}
+
+pub fn unban(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ");
+    if state.banned.remove(&name.to_ascii_lowercase()) {
+        writer.tell(player_id, Line::str(&format!("You unban {}.", name)));
+        audit(player_id, &format!("unban {}", name), writer, state);
+    } else {
+        writer.tell(player_id, Line::str(&format!("{} is not banned.", name)));
+    }
+    Ok(())
+}
+
+pub fn set_var(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    match words.as_slice() {
+        [room_str, var, value_str] => match (find_room(room_str, state), value_str.parse()) {
+            (Some(room_id), Ok(value)) => {
+                state.set_room_var(room_id, var.to_string(), value);
+                writer.tell(
+                    player_id,
+                    Line::str(&format!(
+                        "{} is now {} in room {}.",
+                        var, value, room_id.value
+                    )),
+                );
+                let entry = format!("setvar {} {} {}", room_id.value, var, value);
+                audit(player_id, &entry, writer, state);
+            }
+            (None, _) => writer.tell(player_id, Line::str("There is no such room.")),
+            (_, Err(_)) => writer.tell(player_id, Line::str("The value must be a number.")),
+        },
+        _ => writer.tell(player_id, Line::str("Usage: setvar <room> <var> <value>")),
+    }
+    Ok(())
+}
+
+pub fn spawn(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let template_str = words.join(" ");
+    let template = state
+        .mob_templates
+        .values()
+        .find(|template| {
+            template.id.value.to_string() == template_str || template.matches(&template_str)
+        })
+        .cloned();
+    match template {
+        Some(template) => {
+            let room_id = state.players.by_id().get(&player_id).ok_or("spawn")?.room_id;
+            writer.tell_many(
+                state.players.ids_in_room(room_id),
+                Line::str(&format!("A {} appears.", template.name)),
+            );
+            let entry = format!("spawn {} in {}", template.id.value, room_id.value);
+            spawn_mob(room_id, template, None, state);
+            audit(player_id, &entry, writer, state);
+        }
+        None => writer.tell(player_id, Line::str("There is no such mob template.")),
+    }
+    Ok(())
+}
+
+pub fn slay(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("slay: Self not found")?;
+    let room = state.rooms.get(&player.room_id).ok_or("slay: Room not found")?;
+
+    match resolve_targets_in_room(&words.join(" "), room, &state.mobs).into_iter().next() {
+        Some(RoomTarget::Mob { mob }) => {
+            let (mob_id, room_id) = (mob.id, mob.room_id);
+            let msg = format!("{} slays the {}.", player.name, mob.template.name);
+            writer.tell_many(
+                state.players.ids_in_room(room_id),
+                span(&msg).color(Color::DarkGrey).line(),
+            );
+
+            game_combat::remove_mob(mob_id, state);
+            audit(player_id, &format!("slay {}", mob_id.value), writer, state);
+        }
+        Some(_) => writer.tell(player_id, Line::str("You cannot slay that.")),
+        None => writer.tell(player_id, Line::str("You do not see that here.")),
+    }
+    Ok(())
+}
+
+pub fn restore(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let target_id = if words.is_empty() {
+        Some(player_id)
+    } else {
+        state.players.by_name(&words.join(" ")).map(|target| target.id)
+    };
+    match target_id {
+        Some(target_id) => {
+            let target_name = state.players.modify(&target_id, |target| {
+                target.hp = target.max_hp;
+                target.name.clone()
+            });
+            writer.tell(
+                target_id,
+                span("You feel restored.").color(Color::LightCyan).line(),
+            );
+            if target_id != player_id {
+                writer.tell(
+                    player_id,
+                    Line::str(&format!("You restore {}.", target_name)),
+                );
+            }
+            audit(
+                player_id,
+                &format!("restore {}", target_name),
+                writer,
+                state,
+            );
+        }
+        None => writer.tell(player_id, Line::str("There is no such player online.")),
+    }
+    Ok(())
+}
+
+pub fn announce(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let message = words.join(" ");
+    writer.tell_many(
+        state.players.by_id().keys().copied(),
+        span(&format!("[Announcement] {}", message)).color(Color::Yellow).bold().line(),
+    );
+    audit(player_id, &format!("announce {}", message), writer, state);
+    Ok(())
+}
+
+pub fn audit(player_id: Id<Player>, entry: &str, writer: &mut EventWriter, state: &GameState) {
+    if let Some(player) = state.players.by_id().get(&player_id) {
+        writer.audit(format!(
+            "{} ({}): {}",
+            player.name,
+            player.role.as_str(),
+            entry
+        ));
+    }
+}
+
+fn find_room(str: &str, state: &GameState) -> Option<Id<Room>> {
+    match str.parse::<u64>() {
+        Ok(value) => Some(Id::new(value)).filter(|room_id| state.rooms.contains_key(room_id)),
+        Err(_) => state
+            .rooms
+            .values()
+            .find(|room| room.name.eq_ignore_ascii_case(str))
+            .map(|r| r.id),
+    }
+}
+
+fn teleport(
+    player_id: Id<Player>,
+    to_room_id: Id<Room>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    if !state.rooms.contains_key(&to_room_id) {
+        return Err("teleport: Room not found".to_string());
+    }
+    let (from_room_id, player_name) = state.players.modify(&player_id, |player| {
+        let from_room_id = player.room_id;
+        player.room_id = to_room_id;
+        player.attack_target = None;
+        (from_room_id, player.name.clone())
+    });
+
+    writer.tell_many(
+        state.players.ids_in_room(from_room_id),
+        Line::str(&format!("{} vanishes in a puff of smoke.", player_name)),
+    );
+    writer.tell_many(
+        state.players.ids_in_room_except(to_room_id, player_id),
+        Line::str(&format!("{} appears in a puff of smoke.", player_name)),
+    );
+    arrive_in_room(player_id, to_room_id, writer, state);
+    Ok(())
+}
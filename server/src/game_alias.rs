@@ -1,11 +1,14 @@
+use std::collections::BTreeMap;
+
 use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
 
-use crate::{event_writer::EventWriter, id::Id, line::Line, player::Player};
+use crate::{
+    character, event_writer::EventWriter, game_command, game_state::GameState, id::Id, line::Line,
+    player::Player,
+};
 
-static ALIASES: &[(&str, &str)] = &[
-    ("l", "look"),
-    ("\"", "say"),
-    (":", "emote"),
+static DIRECTION_ALIASES: &[(&str, &str)] = &[
     ("n", "north"),
     ("ne", "northeast"),
     ("e", "east"),
@@ -18,23 +21,180 @@ static ALIASES: &[(&str, &str)] = &[
     ("d", "down"),
 ];
 
-static ALIAS_LINES: Lazy<Vec<Line>> = Lazy::new(|| {
-    ALIASES
-        .iter()
-        .map(|(alias, resolution)| Line::str(&format!("{} → {}", alias, resolution)))
-        .collect::<Vec<_>>()
-});
-
-pub fn resolve_aliases(mut words: Vec<&str>) -> Vec<&str> {
-    for (alias, resolution) in ALIASES {
-        if words[0] == *alias {
-            words[0] = resolution;
-            return words;
+static ALIAS_ARG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$([1-9*])").unwrap());
+
+static MAX_ALIAS_DEPTH: usize = 5;
+static MAX_EXPANDED_COMMANDS: usize = 20;
+static MAX_USER_ALIASES: usize = 50;
+
+/// Expands the player's own aliases, which may refer to each other and contain several
+/// commands separated by `;`. Returns None if the expansion nests or grows too much.
+pub fn expand_user_aliases(
+    command: &str,
+    aliases: &BTreeMap<String, String>,
+) -> Option<Vec<String>> {
+    let mut expanded = Vec::new();
+    if expand_command(command, aliases, 0, &mut expanded) {
+        Some(expanded)
+    } else {
+        None
+    }
+}
+
+fn expand_command(
+    command: &str,
+    aliases: &BTreeMap<String, String>,
+    depth: usize,
+    out: &mut Vec<String>,
+) -> bool {
+    let mut words = command.split_whitespace();
+    let head = words.next().unwrap_or_default().to_ascii_lowercase();
+
+    match aliases.get(&head) {
+        Some(expansion) if depth < MAX_ALIAS_DEPTH => {
+            let args = words.collect::<Vec<_>>();
+            substitute_args(expansion, &args)
+                .split(';')
+                .map(str::trim)
+                .filter(|command| !command.is_empty())
+                .all(|command| expand_command(command, aliases, depth + 1, out))
+        }
+        Some(_) => false,
+        None => {
+            out.push(command.to_string());
+            out.len() <= MAX_EXPANDED_COMMANDS
         }
     }
+}
+
+/// Replaces `$1`..`$9` with the given arguments and `$*` with all of them. Expansions
+/// without placeholders get the arguments appended.
+fn substitute_args(expansion: &str, args: &[&str]) -> String {
+    if ALIAS_ARG_REGEX.is_match(expansion) {
+        ALIAS_ARG_REGEX
+            .replace_all(expansion, |captures: &Captures| match &captures[1] {
+                "*" => args.join(" "),
+                n => n
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| args.get(n - 1))
+                    .map_or(String::new(), |arg| arg.to_string()),
+            })
+            .into_owned()
+    } else if args.is_empty() {
+        expansion.to_string()
+    } else {
+        format!("{} {}", expansion, args.join(" "))
+    }
+}
+
+pub fn resolve_aliases<'a>(mut words: Vec<&'a str>, player: &Player) -> Vec<&'a str> {
+    words[0] = game_command::resolve_alias(words[0], player.role)
+        .unwrap_or_else(|| resolve_direction(words[0]));
     words
 }
 
-pub fn alias(player_id: Id<Player>, writer: &mut EventWriter) {
-    writer.tell_lines(player_id, &ALIAS_LINES);
+pub fn resolve_direction(word: &str) -> &str {
+    DIRECTION_ALIASES
+        .iter()
+        .find(|(alias, _)| word == *alias)
+        .map_or(word, |(_, resolution)| resolution)
+}
+
+pub fn alias(
+    player_id: Id<Player>,
+    mut words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("alias: Self not found")?;
+
+    if words.is_empty() {
+        list_aliases(player, writer);
+        return Ok(());
+    }
+
+    let name = words.remove(0).to_ascii_lowercase();
+    let expansion = words.join(" ");
+    if expansion.is_empty() {
+        let line = match player.aliases.get(&name) {
+            Some(expansion) => format!("{} → {}", name, expansion),
+            None => format!("You have no alias {}.", name),
+        };
+        writer.tell(player_id, Line::str(&line));
+    } else if name.contains(['$', ';']) || name == "alias" || name == "unalias" {
+        writer.tell(player_id, Line::str("You cannot use that as an alias."));
+    } else if !player.aliases.contains_key(&name) && player.aliases.len() >= MAX_USER_ALIASES {
+        writer.tell(player_id, Line::str("You have too many aliases already."));
+    } else {
+        writer.tell(player_id, Line::str(&format!("{} → {}", name, expansion)));
+        state
+            .players
+            .modify(&player_id, |player| player.aliases.insert(name, expansion));
+        save(player_id, writer, state);
+    }
+    Ok(())
+}
+
+pub fn unalias(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ").to_ascii_lowercase();
+    if state
+        .players
+        .modify(&player_id, |player| player.aliases.remove(&name))
+        .is_some()
+    {
+        writer.tell(player_id, Line::str(&format!("Alias {} removed.", name)));
+        save(player_id, writer, state);
+    } else {
+        writer.tell(
+            player_id,
+            Line::str(&format!("You have no alias {}.", name)),
+        );
+    }
+    Ok(())
+}
+
+fn list_aliases(player: &Player, writer: &mut EventWriter) {
+    let command_aliases = game_command::available(player.role)
+        .flat_map(|command| command.aliases.iter().map(move |alias| (*alias, command.name)));
+    let mut lines = command_aliases
+        .chain(DIRECTION_ALIASES.iter().copied())
+        .map(|(alias, resolution)| Line::str(&format!("{} → {}", alias, resolution)))
+        .collect::<Vec<_>>();
+
+    if !player.aliases.is_empty() {
+        lines.push(Line::str("Your own aliases:"));
+        lines.extend(
+            player
+                .aliases
+                .iter()
+                .map(|(alias, expansion)| Line::str(&format!("{} → {}", alias, expansion))),
+        );
+    }
+    writer.tell_lines(player.id, &lines);
+}
+
+fn save(player_id: Id<Player>, writer: &mut EventWriter, state: &GameState) {
+    if let Some(player) = state.players.by_id().get(&player_id) {
+        character::save_player(player, writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_expand_into_several_commands() {
+        let aliases = BTreeMap::from([("k".to_string(), "kill $1; say Die, $1!".to_string())]);
+        assert_eq!(
+            expand_user_aliases("k rat", &aliases),
+            Some(vec!["kill rat".to_string(), "say Die, rat!".to_string()])
+        );
+    }
 }
@@ -0,0 +1,327 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    event_writer::EventWriter,
+    game_admin::audit,
+    game_alias::resolve_direction,
+    game_room::reverse_direction,
+    game_spawn::populate_spawn,
+    game_state::{GameState, Room, RoomDescription, RoomExit, RoomObject},
+    id::Id,
+    line::Line,
+    mob::{MobSpawn, SpawnOrigin},
+    named::Named,
+    player::Player,
+    zone::Zone,
+};
+
+pub static ROOMS_PATH: &str = "data/rooms.yaml";
+
+pub fn create_room(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let room_id = state.players.by_id().get(&player_id).ok_or("rcreate: Self not found")?.room_id;
+    let zone_id = state.rooms.get(&room_id).ok_or("rcreate: Room not found")?.zone_id;
+    let room_id = insert_new_room(&words.join(" "), zone_id, state);
+    writer.tell(
+        player_id,
+        Line::str(&format!("You create room {}.", room_id.value)),
+    );
+    audit(
+        player_id,
+        &format!("rcreate {}", room_id.value),
+        writer,
+        state,
+    );
+    Ok(())
+}
+
+pub fn dig(
+    player_id: Id<Player>,
+    mut words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let room_id = state.players.by_id().get(&player_id).ok_or("dig: Self not found")?.room_id;
+    let direction = resolve_direction(words.remove(0)).to_string();
+    let room = state.rooms.get(&room_id).ok_or("dig: Room not found")?;
+    let zone_id = room.zone_id;
+    if room.exits.contains_key(&direction) {
+        writer.tell(
+            player_id,
+            Line::str(&format!("There is already an exit {}.", direction)),
+        );
+        return Ok(());
+    }
+
+    let to_room_id = match words.join(" ").parse::<u64>() {
+        Ok(value) if state.rooms.contains_key(&Id::new(value)) => Id::new(value),
+        Ok(_) => {
+            writer.tell(player_id, Line::str("There is no such room."));
+            return Ok(());
+        }
+        Err(_) => insert_new_room(&words.join(" "), zone_id, state),
+    };
+
+    modify_room(room_id, state, |room| {
+        room.exits.insert(direction.clone(), RoomExit::Static(to_room_id));
+    })?;
+    let reverse = reverse_direction(&direction).filter(|reverse| {
+        state
+            .rooms
+            .get(&to_room_id)
+            .is_some_and(|room| !room.exits.contains_key(*reverse))
+    });
+    if let Some(reverse) = reverse {
+        modify_room(to_room_id, state, |room| {
+            room.exits.insert(reverse.to_string(), RoomExit::Static(room_id));
+        })?;
+    }
+
+    let to_room_name = state.rooms.get(&to_room_id).map_or("???", |r| &r.name);
+    writer.tell(
+        player_id,
+        Line::str(&format!(
+            "You dig {} to {} (room {}).",
+            direction, to_room_name, to_room_id.value
+        )),
+    );
+    let entry = format!(
+        "dig {} from {} to {}",
+        direction, room_id.value, to_room_id.value
+    );
+    audit(player_id, &entry, writer, state);
+    Ok(())
+}
+
+pub fn edit_room(
+    player_id: Id<Player>,
+    mut words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let room_id = state.players.by_id().get(&player_id).ok_or("redit: Self not found")?.room_id;
+    let field = words.remove(0).to_ascii_lowercase();
+    let args = words.join(" ");
+
+    let result = match (field.as_str(), words.as_slice()) {
+        ("name", [_, ..]) => {
+            modify_room(room_id, state, |room| room.name = args.clone())?;
+            Ok(format!("Room name set to {}.", args))
+        }
+        ("desc", [_, ..]) => {
+            modify_room(room_id, state, |room| {
+                room.description = RoomDescription::Static(args.clone())
+            })?;
+            Ok("Room description set.".to_string())
+        }
+        ("object", [action, ..]) => {
+            let args = words[1..].join(" ");
+            edit_object(room_id, &action.to_ascii_lowercase(), &args, state)
+        }
+        ("spawn", [action, ..]) => {
+            let args = words[1..].join(" ");
+            edit_mob_spawn(room_id, &action.to_ascii_lowercase(), &args, state)
+        }
+        ("exit", ["remove", direction]) => {
+            let direction = resolve_direction(direction);
+            if modify_room(room_id, state, |room| room.exits.remove(direction))?.is_some() {
+                Ok(format!("Exit {} removed.", direction))
+            } else {
+                Err(format!("There is no exit {}.", direction))
+            }
+        }
+        _ => Err(
+            "Usage: redit name|desc <text>, redit object add|remove|desc|alias, \
+             redit spawn add|remove <template>, redit exit remove <direction>"
+                .to_string(),
+        ),
+    };
+
+    match result {
+        Ok(message) => {
+            writer.tell(player_id, Line::str(&message));
+            let entry = format!("redit {} {} in {}", field, args, room_id.value);
+            audit(player_id, &entry, writer, state);
+        }
+        Err(message) => writer.tell(player_id, Line::str(&message)),
+    }
+    Ok(())
+}
+
+fn edit_object(
+    room_id: Id<Room>,
+    action: &str,
+    args: &str,
+    state: &mut GameState,
+) -> Result<String, String> {
+    let (name, value) = match args.split_once('=') {
+        Some((name, value)) => (name.trim(), value.trim()),
+        None => (args.trim(), ""),
+    };
+    if name.is_empty() {
+        return Err("Which object?".to_string());
+    }
+    let room = state.rooms.get(&room_id).ok_or("There is no room here.")?;
+    let index = room.objects.iter().position(|obj| obj.matches(name));
+
+    match (action, index) {
+        ("add", None) => {
+            let object = RoomObject {
+                name: name.to_string(),
+                aliases: vec![],
+                description: RoomDescription::Static("You see nothing special.".to_string()),
+                commands: vec![],
+            };
+            modify_room(room_id, state, |room| room.objects.push(object))?;
+            Ok(format!("Object {} added.", name))
+        }
+        ("add", Some(_)) => Err(format!("There is already a {} here.", name)),
+        ("remove", Some(index)) => {
+            modify_room(room_id, state, |room| room.objects.remove(index))?;
+            Ok(format!("Object {} removed.", name))
+        }
+        ("desc", Some(index)) if !value.is_empty() => {
+            let description = RoomDescription::Static(value.to_string());
+            modify_room(room_id, state, |room| {
+                room.objects[index].description = description
+            })?;
+            Ok(format!("Description of {} set.", name))
+        }
+        ("alias", Some(index)) if !value.is_empty() => {
+            modify_room(room_id, state, |room| {
+                room.objects[index].aliases.push(value.to_string())
+            })?;
+            Ok(format!("Alias {} added to {}.", value, name))
+        }
+        ("remove" | "desc" | "alias", None) => Err(format!("There is no {} here.", name)),
+        _ => Err(
+            "Usage: redit object add|remove <name>, redit object desc|alias <name> = <text>"
+                .to_string(),
+        ),
+    }
+}
+
+fn edit_mob_spawn(
+    room_id: Id<Room>,
+    action: &str,
+    template_str: &str,
+    state: &mut GameState,
+) -> Result<String, String> {
+    let template = state
+        .mob_templates
+        .values()
+        .find(|template| {
+            template.id.value.to_string() == template_str || template.matches(template_str)
+        })
+        .cloned()
+        .ok_or("There is no such mob template.")?;
+
+    match action {
+        "add" => {
+            let spawn = MobSpawn::new(template.id);
+            let index = modify_room(room_id, state, |room| {
+                room.mob_spawns.push(spawn);
+                room.mob_spawns.len() - 1
+            })?;
+            populate_spawn(SpawnOrigin { room_id, index }, state);
+            Ok(format!("A {} will now spawn here.", template.name))
+        }
+        "remove" => {
+            let removed = modify_room(room_id, state, |room| {
+                let index = room
+                    .mob_spawns
+                    .iter()
+                    .position(|spawn| spawn.mob_template_id == template.id)?;
+                room.mob_spawns.remove(index);
+                Some(index)
+            })?;
+            match removed {
+                Some(index) => {
+                    forget_spawn(SpawnOrigin { room_id, index }, state);
+                    Ok(format!("One {} spawn removed.", template.name))
+                }
+                None => Err(format!("No {} spawns here.", template.name)),
+            }
+        }
+        _ => Err("Usage: redit spawn add|remove <template>".to_string()),
+    }
+}
+
+/// Detaches the mobs of a removed spawn and renumbers the origins of the spawns after it.
+fn forget_spawn(removed: SpawnOrigin, state: &mut GameState) {
+    let mob_ids = state.mobs.by_id().keys().copied().collect::<Vec<_>>();
+    for mob_id in mob_ids {
+        state.mobs.modify(&mob_id, |mob| match mob.spawn_origin {
+            Some(origin) if origin == removed => mob.spawn_origin = None,
+            Some(SpawnOrigin { room_id, index })
+                if room_id == removed.room_id && index > removed.index =>
+            {
+                mob.spawn_origin = Some(SpawnOrigin { room_id, index: index - 1 });
+            }
+            _ => (),
+        });
+    }
+    for origins in state.scheduled_mob_spawns.values_mut() {
+        origins.retain(|origin| *origin != removed);
+        for origin in origins.iter_mut() {
+            if origin.room_id == removed.room_id && origin.index > removed.index {
+                origin.index -= 1;
+            }
+        }
+    }
+}
+
+pub fn save_rooms(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &GameState,
+) -> Result<(), String> {
+    let mut rooms = state.rooms.values().collect::<Vec<_>>();
+    rooms.sort_by_key(|room| room.id.value);
+
+    let yaml = serde_yaml::to_string(&rooms).map_err(|err| format!("rsave: {}", err))?;
+    writer.write_file(ROOMS_PATH, yaml);
+    writer.tell(
+        player_id,
+        Line::str(&format!("Saved {} rooms.", rooms.len())),
+    );
+    audit(player_id, "rsave", writer, state);
+    Ok(())
+}
+
+fn insert_new_room(name: &str, zone_id: Id<Zone>, state: &mut GameState) -> Id<Room> {
+    let id = Id::new(state.rooms.keys().map(|id| id.value + 1).max().unwrap_or(0));
+    let name = if name.is_empty() { "New room" } else { name };
+    let room = Room {
+        id,
+        name: name.to_string(),
+        zone_id,
+        description: RoomDescription::Static("This room has not been described yet.".to_string()),
+        exits: BTreeMap::new(),
+        objects: vec![],
+        mob_spawns: vec![],
+        coords: None,
+        vars: BTreeMap::new(),
+        flags: BTreeSet::new(),
+    };
+    state.rooms.insert(id, room);
+    state.map_version += 1;
+    id
+}
+
+fn modify_room<T>(
+    room_id: Id<Room>,
+    state: &mut GameState,
+    f: impl FnOnce(&mut Room) -> T,
+) -> Result<T, String> {
+    let room = state
+        .rooms
+        .get_mut(&room_id)
+        .ok_or_else(|| format!("Room {} not found", room_id.value))?;
+    state.map_version += 1;
+    Ok(f(room))
+}
@@ -2,29 +2,89 @@ use std::ops::Add;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Deserializer};
 
 use crate::{
+    character,
     event_writer::EventWriter,
-    game_state::GameState,
+    game_admin::audit,
+    game_state::{GameState, Room},
+    id::Id,
     line::{span, Color, Line},
     player::Player,
+    text_util::{and_spans, parse_duration_secs},
+    tick::TickDuration,
 };
 
 static ILLEGAL_CHAT_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\p{Extended_Pictographic}").unwrap());
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatConfig {
+    #[serde(default)]
+    pub word_filter: WordFilter,
+    pub flood: FloodConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloodConfig {
+    pub max_messages: usize,
+    #[serde(
+        rename = "windowSecs",
+        deserialize_with = "TickDuration::deserialize_from_secs"
+    )]
+    pub window: TickDuration,
+    pub max_repeats: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WordFilter(Option<Regex>);
+
+impl WordFilter {
+    pub fn apply(&self, str: &str) -> String {
+        match &self.0 {
+            Some(regex) => regex
+                .replace_all(str, |captures: &regex::Captures| {
+                    "*".repeat(captures[0].len())
+                })
+                .into_owned(),
+            None => str.to_string(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WordFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let words = Vec::<String>::deserialize(deserializer)?;
+        if words.is_empty() {
+            Ok(WordFilter(None))
+        } else {
+            let alternatives = words.iter().map(|word| regex::escape(word)).collect::<Vec<_>>();
+            Regex::new(&format!(r"(?i)\b({})\b", alternatives.join("|")))
+                .map(|regex| WordFilter(Some(regex)))
+                .map_err(serde::de::Error::custom)
+        }
+    }
+}
+
 pub enum ChatCommand {
     Say,
     Emote,
 }
 
 pub fn chat(
-    player: &Player,
+    player_id: Id<Player>,
     words: Vec<&str>,
     kind: ChatCommand,
     writer: &mut EventWriter,
-    state: &GameState,
-) {
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("chat: Self not found")?;
     let mut words_joined = words.join(" ");
     if words_joined.len() > 128 {
         writer.tell(player.id, Line::str("That message is too long."));
@@ -33,7 +93,24 @@ pub fn chat(
             player.id,
             Line::str("That message contains illegal characters."),
         );
+    } else if is_muted(&player.name, state) {
+        writer.tell(player.id, Line::str("You are muted and cannot talk."));
+    } else if is_flooding(player, &words_joined, state) {
+        writer.tell(
+            player.id,
+            Line::str("You are talking too much. Wait a moment before speaking again."),
+        );
     } else {
+        let flood_window = state.chat_config.flood.window;
+        let ticks = state.ticks;
+        let message = words_joined.clone();
+        state.players.modify(&player_id, |player| {
+            player.recent_chat.retain(|(tick, _)| *tick + flood_window > ticks);
+            player.recent_chat.push_back((ticks, message));
+        });
+        let player = state.players.by_id().get(&player_id).ok_or("chat: Self not found")?;
+
+        words_joined = state.chat_config.word_filter.apply(&words_joined);
         if let ChatCommand::Say = kind {
             let mut chars = words_joined.chars();
             if let Some(first_char) = chars.next() {
@@ -60,9 +137,195 @@ pub fn chat(
         })
         .color(Color::Yellow)
         .line();
-        writer.tell_many(
-            state.players.ids_in_room_except(player.room_id, player.id),
-            to_others,
+        writer.tell_many(listeners_in_room(player, player.room_id, state), to_others);
+    }
+    Ok(())
+}
+
+/// Players in the room who hear the speaker, i.e. everyone else not ignoring them.
+pub fn listeners_in_room<'a>(
+    speaker: &'a Player,
+    room_id: Id<Room>,
+    state: &'a GameState,
+) -> impl Iterator<Item = Id<Player>> + 'a {
+    state.players.ids_in_room_except(room_id, speaker.id).filter(move |id| {
+        state
+            .players
+            .by_id()
+            .get(id)
+            .is_none_or(|listener| !listener.is_ignoring(speaker))
+    })
+}
+
+fn is_muted(player_name: &str, state: &GameState) -> bool {
+    match state.mutes.get(&player_name.to_ascii_lowercase()) {
+        Some(Some(until)) => *until > state.ticks,
+        Some(None) => true,
+        None => false,
+    }
+}
+
+fn is_flooding(player: &Player, message: &str, state: &GameState) -> bool {
+    let flood = &state.chat_config.flood;
+    let recent = player
+        .recent_chat
+        .iter()
+        .filter(|(tick, _)| *tick + flood.window > state.ticks)
+        .collect::<Vec<_>>();
+    let repeats = recent.iter().filter(|(_, recent_message)| recent_message == message).count();
+    recent.len() >= flood.max_messages || repeats >= flood.max_repeats
+}
+
+pub fn ignore(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("ignore: Self not found")?;
+
+    if words.is_empty() {
+        if player.ignored.is_empty() {
+            writer.tell(player_id, Line::str("You are not ignoring anyone."));
+        } else {
+            let mut names = player.ignored.iter().collect::<Vec<_>>();
+            names.sort();
+            let names = names.into_iter().map(|name| span(name).color(Color::Blue)).collect();
+            let line = Line::str("You are ignoring ").extend(and_spans(names)).push(span("."));
+            writer.tell(player_id, line);
+        }
+    } else {
+        let name = words.join(" ");
+        if name.eq_ignore_ascii_case(&player.name) {
+            writer.tell(player_id, Line::str("You cannot ignore yourself."));
+        } else if !is_known_player(&name, state) {
+            writer.tell(
+                player_id,
+                Line::str(&format!("There is no player named {}.", name)),
+            );
+        } else {
+            let key = name.to_ascii_lowercase();
+            state.players.modify(&player_id, |player| player.ignored.insert(key));
+            save(player_id, writer, state);
+            writer.tell(
+                player_id,
+                Line::str(&format!("You are now ignoring {}.", name)),
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn unignore(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ");
+    let key = name.to_ascii_lowercase();
+    if state.players.modify(&player_id, |player| player.ignored.remove(&key)) {
+        save(player_id, writer, state);
+        writer.tell(
+            player_id,
+            Line::str(&format!("You are no longer ignoring {}.", name)),
+        );
+    } else {
+        writer.tell(
+            player_id,
+            Line::str(&format!("You are not ignoring {}.", name)),
+        );
+    }
+    Ok(())
+}
+
+pub fn mute(
+    player_id: Id<Player>,
+    mut words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let duration_secs = match words.last().and_then(|word| parse_duration_secs(word)) {
+        Some(secs) if words.len() > 1 => {
+            words.pop();
+            Some(secs)
+        }
+        _ => None,
+    };
+    let name = words.join(" ");
+    if !is_known_player(&name, state) {
+        writer.tell(
+            player_id,
+            Line::str(&format!("There is no player named {}.", name)),
+        );
+        return Ok(());
+    }
+
+    let until = duration_secs.map(|secs| state.ticks + TickDuration::from_secs(secs));
+    state.mutes.insert(name.to_ascii_lowercase(), until);
+
+    let msg_self = match duration_secs {
+        Some(secs) => format!("You mute {} for {} seconds.", name, secs),
+        None => format!("You mute {} until further notice.", name),
+    };
+    writer.tell(player_id, Line::str(&msg_self));
+    if let Some(target) = state.players.by_name(&name) {
+        writer.tell(
+            target.id,
+            span("You have been muted by a moderator.").color(Color::LightRed).line(),
         );
     }
+    let entry = match duration_secs {
+        Some(secs) => format!("mute {} {}s", name, secs),
+        None => format!("mute {}", name),
+    };
+    audit(player_id, &entry, writer, state);
+    Ok(())
+}
+
+pub fn unmute(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let name = words.join(" ");
+    if state.mutes.remove(&name.to_ascii_lowercase()).is_some() {
+        writer.tell(player_id, Line::str(&format!("You unmute {}.", name)));
+        if let Some(target) = state.players.by_name(&name) {
+            writer.tell(target.id, Line::str("You are no longer muted."));
+        }
+        audit(player_id, &format!("unmute {}", name), writer, state);
+    } else {
+        writer.tell(player_id, Line::str(&format!("{} is not muted.", name)));
+    }
+    Ok(())
+}
+
+/// Whether the name belongs to a player who is online or has an account.
+fn is_known_player(name: &str, state: &GameState) -> bool {
+    state.players.by_name(name).is_some() || state.accounts.contains_key(&name.to_ascii_lowercase())
+}
+
+fn save(player_id: Id<Player>, writer: &mut EventWriter, state: &GameState) {
+    if let Some(player) = state.players.by_id().get(&player_id) {
+        character::save_player(player, writer);
+    }
+}
+
+pub fn tick_expire_mutes(writer: &mut EventWriter, state: &mut GameState) {
+    let expired = state
+        .mutes
+        .iter()
+        .filter_map(|(name, until)| match until {
+            Some(until) if *until <= state.ticks => Some(name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for name in expired {
+        state.mutes.remove(&name);
+        if let Some(player) = state.players.by_name(&name) {
+            writer.tell(player.id, Line::str("You are no longer muted."));
+        }
+    }
 }
@@ -1,20 +1,33 @@
 use std::collections::HashSet;
 
 use crate::{
+    character,
+    damage::{self, DamageType, HitOutcome},
     event_writer::EventWriter,
-    game_room::{self, describe_room, RoomTarget},
-    game_state::GameState,
+    game_logic,
+    game_room::{self, arrive_in_room, RoomTarget},
+    game_spawn,
+    game_state::{GameState, Room},
+    game_zone,
     id::{Id, IdMap},
     line::{span, Color, Line},
     mob::Mob,
-    player::Player,
+    player::{AttackTarget, Player},
     player_coll::PlayerColl,
-    tick::TickDuration,
+    status_effect::{StatusApplication, StatusEffects, StatusKind},
+    tick::{Tick, TickDuration},
 };
+use log::warn;
 use once_cell::sync::Lazy;
-use rand::{thread_rng, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 
 pub static PLAYER_ATTACK_FREQ: Lazy<TickDuration> = Lazy::new(|| TickDuration::from_secs(1.5));
+static PLAYER_DAMAGE: i32 = 10;
+static PLAYER_DAMAGE_TYPE: DamageType = DamageType::Slashing;
+static PLAYER_ACCURACY: i32 = 0;
+static PLAYER_DEFENSE: i32 = 0;
+static FLEE_FAILURE_CHANCE: f64 = 0.3;
+static FLEE_STUMBLE_SECS: f32 = 1.0;
 pub static PLAYER_HEAL_FREQ: Lazy<TickDuration> = Lazy::new(|| TickDuration::from_secs(3.0));
 
 pub fn kill(
@@ -23,14 +36,19 @@ pub fn kill(
     writer: &mut EventWriter,
     state: &mut GameState,
 ) -> Result<(), String> {
-    let GameState { players, rooms, mobs, .. } = state;
+    let player = state.players.by_id().get(&player_id).ok_or("kill: Self not found")?;
+    if game_zone::is_safe_room(player.room_id, state) {
+        writer.tell(player_id, Line::str("You cannot fight here."));
+        return Ok(());
+    }
 
+    let GameState { players, rooms, mobs, .. } = state;
     let player = players.by_id().get(&player_id).ok_or("kill: Self not found")?;
     let room = rooms.get(&player.room_id).ok_or("kill: Room not found")?;
 
     let args_joined = args.join(" ");
 
-    match game_room::resolve_target_in_room(&args_joined, room, mobs.by_id()) {
+    match game_room::resolve_targets_in_room(&args_joined, room, mobs).into_iter().next() {
         Some(RoomTarget::Mob { mob }) => {
             let msg_self = format!("You attack the {}.", mob.template.name);
             writer.tell(player_id, span(&msg_self).color(Color::LightCyan).line());
@@ -42,34 +60,240 @@ pub fn kill(
             );
 
             let mob_id = mob.id;
-            players.modify(&player_id, |player| player.attack_target = Some(mob_id));
-
-            let mob_ids_in_room = mobs.by_room_id().get(&room.id).cloned().unwrap_or_default();
-            for mob_id in mob_ids_in_room {
-                mobs.modify(&mob_id, |mob| {
-                    mob.hostile_to.insert(player_id);
-                });
-            }
+            players.modify(&player_id, |player| {
+                player.attack_target = Some(AttackTarget::Mob(mob_id))
+            });
+            provoke(mob_id, player_id, writer, state);
         }
         Some(_) => {
             writer.tell(player_id, Line::str("You cannot kill that."));
         }
         None => {
-            writer.tell(player_id, Line::str("You do not see that here."));
+            let target_id = players
+                .by_name(&args_joined)
+                .filter(|target| target.room_id == room.id)
+                .map(|target| target.id);
+            match target_id {
+                Some(target_id) => attack_player(player_id, target_id, writer, state)?,
+                None => writer.tell(player_id, Line::str("You do not see that here.")),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Starts a fight against another player, if both opted in to PvP or the room allows it anyway.
+fn attack_player(
+    player_id: Id<Player>,
+    target_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    if target_id == player_id {
+        writer.tell(player_id, Line::str("You cannot attack yourself."));
+        return Ok(());
+    }
+    let player = state.players.by_id().get(&player_id).ok_or("kill: Self not found")?;
+    let target = state.players.by_id().get(&target_id).ok_or("kill: Target not found")?;
+    if !game_zone::is_pvp_room(player.room_id, state) {
+        if !player.pvp {
+            writer.tell(
+                player_id,
+                Line::str("You have not opted in to fighting other players. Use pvp on first."),
+            );
+            return Ok(());
+        }
+        if !target.pvp {
+            let msg = format!(
+                "{} has not opted in to fighting other players.",
+                target.name
+            );
+            writer.tell(player_id, Line::str(&msg));
+            return Ok(());
+        }
+    }
+
+    let msg_self = format!("You attack {}!", target.name);
+    writer.tell(player_id, span(&msg_self).color(Color::LightCyan).line());
+    let msg_target = format!("{} attacks you!", player.name);
+    writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+    let msg_others = format!("{} attacks {}.", player.name, target.name);
+    writer.tell_many(
+        state
+            .players
+            .ids_in_room_except(player.room_id, player_id)
+            .filter(|id| *id != target_id),
+        span(&msg_others).color(Color::Cyan).line(),
+    );
+    state.players.modify(&player_id, |player| {
+        player.attack_target = Some(AttackTarget::Player(target_id))
+    });
+    Ok(())
+}
+
+pub fn pvp(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("pvp: Self not found")?;
+    let pvp = match words.as_slice() {
+        [] => {
+            let msg = if player.pvp {
+                "You are open to fights with other players."
+            } else {
+                "You do not fight other players, except where PvP is allowed for everyone."
+            };
+            writer.tell(player_id, Line::str(msg));
+            return Ok(());
         }
+        ["on"] => true,
+        ["off"] => false,
+        _ => {
+            writer.tell(player_id, Line::str("Usage: pvp [on|off]"));
+            return Ok(());
+        }
+    };
+    if !pvp && is_in_player_fight(player_id, state) {
+        writer.tell(
+            player_id,
+            Line::str("You cannot back out in the middle of a fight."),
+        );
+        return Ok(());
+    }
+
+    let msg = if pvp {
+        "You are now open to fights with other players."
+    } else {
+        "You no longer fight other players."
+    };
+    writer.tell(player_id, Line::str(msg));
+    state.players.modify(&player_id, |player| player.pvp = pvp);
+    if let Some(player) = state.players.by_id().get(&player_id) {
+        character::save_player(player, writer);
     }
     Ok(())
 }
 
+/// Whether the player is attacking another player, or being attacked by one.
+fn is_in_player_fight(player_id: Id<Player>, state: &GameState) -> bool {
+    state.players.by_id().values().any(|player| match player.attack_target {
+        Some(AttackTarget::Player(target_id)) => player.id == player_id || target_id == player_id,
+        _ => false,
+    })
+}
+
+/// Makes an attacked mob hostile to the attacker, together with the mobs of its faction in the
+/// room. A mob that calls for help also brings the idle mobs of its faction from the neighbouring
+/// rooms, the first time it is provoked.
+pub fn provoke(
+    mob_id: Id<Mob>,
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) {
+    let Some(mob) = state.mobs.by_id().get(&mob_id) else {
+        return;
+    };
+    let room_id = mob.room_id;
+    let template = mob.template.clone();
+    let first_provoked = mob.hostile_to.is_empty();
+
+    let mut helper_ids = state
+        .mobs
+        .by_room_id()
+        .get(&room_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|id| state.mobs.by_id().get(id))
+        .filter(|helper| helper.id != mob_id && helper.template.assists(&template))
+        .map(|helper| helper.id)
+        .collect::<Vec<_>>();
+
+    if template.calls_for_help && first_provoked {
+        let neighbour_room_ids = state
+            .rooms
+            .get(&room_id)
+            .map(|room| {
+                game_room::open_exits(room, state)
+                    .map(|(_, to)| to)
+                    .filter(|to| *to != room_id)
+                    .collect::<HashSet<_>>()
+            })
+            .unwrap_or_default();
+        let called = state
+            .mobs
+            .by_id()
+            .values()
+            .filter(|helper| {
+                neighbour_room_ids.contains(&helper.room_id)
+                    && helper.attack_target.is_none()
+                    && helper.template.assists(&template)
+            })
+            .map(|helper| (helper.id, helper.room_id, helper.template.name.clone()))
+            .collect::<Vec<_>>();
+
+        let msg = format!("The {} calls for help!", template.name);
+        writer.tell_many(
+            state.players.ids_in_room(room_id),
+            span(&msg).color(Color::Red).line(),
+        );
+        for (helper_id, from_room_id, helper_name) in called {
+            state.mobs.modify(&helper_id, |helper| helper.room_id = room_id);
+            writer.tell_many(
+                state.players.ids_in_room(from_room_id),
+                Line::str(&format!("The {} hurries away.", helper_name)),
+            );
+            let msg = format!("A {} rushes in to help the {}.", helper_name, template.name);
+            writer.tell_many(
+                state.players.ids_in_room(room_id),
+                span(&msg).color(Color::Red).line(),
+            );
+            helper_ids.push(helper_id);
+        }
+    }
+
+    for id in std::iter::once(mob_id).chain(helper_ids) {
+        state.mobs.modify(&id, |mob| {
+            mob.hostile_to.insert(player_id);
+        });
+    }
+}
+
+/// Removes a dead mob, stops everyone from attacking it and schedules its respawn.
+pub fn remove_mob(mob_id: Id<Mob>, state: &mut GameState) {
+    let Some(mob) = state.mobs.remove(&mob_id) else {
+        return;
+    };
+    if let Some(origin) = mob.spawn_origin {
+        game_spawn::schedule_respawn(origin, state);
+    }
+    let attackers = state
+        .players
+        .by_id()
+        .values()
+        .filter(|p| p.attack_target == Some(AttackTarget::Mob(mob_id)))
+        .map(|p| p.id)
+        .collect::<Vec<_>>();
+    for attacker_id in attackers {
+        state.players.modify(&attacker_id, |p| p.attack_target = None);
+    }
+}
+
 pub fn tick_player_attacks(writer: &mut EventWriter, state: &mut GameState) {
-    let GameState { ticks, players, mobs, scheduled_mob_spawns, .. } = state;
+    let GameState { ticks, players, mobs, rng, .. } = state;
 
     let mut killed_mob_ids = Vec::new();
+    let mut killed_players = Vec::new();
 
     let players_on_this_tick = players
         .by_id()
         .values()
-        .filter(|player| ticks.is_on_division(*PLAYER_ATTACK_FREQ, player.attack_offset))
+        .filter(|player| {
+            let period = attack_period(*PLAYER_ATTACK_FREQ, &player.status_effects, *ticks);
+            ticks.is_on_division(period, player.attack_offset) && !player.is_stunned(*ticks)
+        })
         .map(|player| player.id)
         .collect::<Vec<_>>();
 
@@ -79,43 +303,74 @@ pub fn tick_player_attacks(writer: &mut EventWriter, state: &mut GameState) {
 
     for player_id in &players_on_this_tick {
         let player = players.by_id().get(player_id).unwrap_or_else(|| unreachable!());
-        if let Some(target_mob_id) = player.attack_target {
-            match mobs.by_id().get(&target_mob_id) {
+        match player.attack_target {
+            Some(AttackTarget::Mob(target_mob_id)) if killed_mob_ids.contains(&target_mob_id) => (),
+            Some(AttackTarget::Mob(target_mob_id)) => match mobs.by_id().get(&target_mob_id) {
                 Some(mob) if mob.room_id == player.room_id => {
                     let mob_id = mob.id;
-                    let mob_room_id = mob.room_id;
-                    let mob_template_id = mob.template.id;
                     let killed = mobs.modify(&mob_id, |mob| {
-                        attack_with_player(player, mob, players, writer)
+                        attack_with_player(player, mob, *ticks, rng, players, writer)
                     });
                     if killed {
-                        let respawn_at = *ticks + TickDuration::from_secs(30.0);
-                        scheduled_mob_spawns.insert(respawn_at, (mob_room_id, mob_template_id));
-
-                        mobs.remove(&target_mob_id);
                         killed_mob_ids.push(target_mob_id);
                     }
                 }
                 _ => {
                     players.modify(player_id, |p| p.attack_target = None);
                 }
+            },
+            Some(AttackTarget::Player(target_id)) => {
+                let in_reach = players
+                    .by_id()
+                    .get(&target_id)
+                    .is_some_and(|target| target.room_id == player.room_id);
+                if killed_players.iter().any(|(victim_id, _)| *victim_id == target_id) {
+                    continue;
+                }
+                if !in_reach {
+                    players.modify(player_id, |p| p.attack_target = None);
+                } else if attack_player_with_player(
+                    *player_id, target_id, *ticks, rng, players, writer,
+                ) {
+                    killed_players.push((target_id, *player_id));
+                }
             }
+            None => (),
         }
     }
 
-    let players_attacking_killed_mobs = players
-        .by_id()
-        .values()
-        .filter_map(|player| match player.attack_target {
-            Some(target_mob_id) if killed_mob_ids.contains(&target_mob_id) => Some(player.id),
-            _ => None,
-        })
-        .collect::<Vec<_>>();
-    for player_id in players_attacking_killed_mobs {
-        players.modify(&player_id, |p| p.attack_target = None);
+    for mob_id in killed_mob_ids {
+        remove_mob(mob_id, state);
+    }
+    for (victim_id, killer_id) in killed_players {
+        credit_player_kill(victim_id, killer_id, writer, state);
+        respawn_player(victim_id, writer, state);
     }
 }
 
+/// Counts the kill for the killer and tells everyone online about it.
+fn credit_player_kill(
+    victim_id: Id<Player>,
+    killer_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) {
+    let Some(victim) = state.players.by_id().get(&victim_id) else {
+        return;
+    };
+    let victim_name = victim.name.clone();
+    let killer_name = state.players.modify(&killer_id, |killer| {
+        killer.player_kills += 1;
+        killer.attack_target = None;
+        killer.name.clone()
+    });
+    let msg = format!("{} has been slain by {}!", victim_name, killer_name);
+    writer.tell_many(
+        state.players.by_id().keys().copied(),
+        span(&msg).color(Color::Red).bold().line(),
+    );
+}
+
 fn update_player_target(
     player_id: Id<Player>,
     mobs: &IdMap<Mob>,
@@ -127,36 +382,116 @@ fn update_player_target(
     if player.attack_target.is_none() {
         let next_target = mobs
             .values()
-            .find(|mob| mob.room_id == player.room_id && mob.hostile_to.contains(&player.id));
+            .find(|mob| mob.room_id == player.room_id && mob.hostile_to.contains(&player.id))
+            .map(|mob| {
+                (
+                    AttackTarget::Mob(mob.id),
+                    format!("the {}", mob.template.name),
+                )
+            })
+            .or_else(|| {
+                // Fights back against players attacking them.
+                players
+                    .by_id()
+                    .values()
+                    .find(|other| {
+                        other.room_id == room_id
+                            && other.attack_target == Some(AttackTarget::Player(player_id))
+                    })
+                    .map(|other| (AttackTarget::Player(other.id), other.name.clone()))
+            });
 
-        if let Some(mob) = next_target {
-            let msg_self = format!("You attack the {}.", mob.template.name);
+        if let Some((target, target_name)) = next_target {
+            let msg_self = format!("You attack {}.", target_name);
             writer.tell(player.id, span(&msg_self).color(Color::LightCyan).line());
-            let msg_others = format!("{} attacks the {}.", &player.name, mob.template.name);
+            let target_player_id = match target {
+                AttackTarget::Player(target_id) => Some(target_id),
+                AttackTarget::Mob(_) => None,
+            };
+            if let Some(target_id) = target_player_id {
+                let msg_target = format!("{} attacks you.", &player.name);
+                writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+            }
+            let msg_others = format!("{} attacks {}.", &player.name, target_name);
             writer.tell_many(
-                players.ids_in_room_except(room_id, player_id),
+                players
+                    .ids_in_room_except(room_id, player_id)
+                    .filter(|id| Some(*id) != target_player_id),
                 span(&msg_others).color(Color::Cyan).line(),
             );
 
-            players.modify(&player_id, |p| p.attack_target = Some(mob.id));
+            players.modify(&player_id, |p| p.attack_target = Some(target));
         }
     }
 }
 
+/// The share of their current HP a player is expected to lose while killing the mob in a straight
+/// fight. Above 1 the player is likely to die first.
+pub fn expected_hp_loss(player: &Player, mob: &Mob) -> f64 {
+    let template = &mob.template;
+    let player_damage = template.damage_modifiers.apply(PLAYER_DAMAGE, PLAYER_DAMAGE_TYPE);
+    let player_dps = damage::expected_damage(player_damage, PLAYER_ACCURACY, template.defense)
+        / f64::from(PLAYER_ATTACK_FREQ.as_secs());
+    let mob_dps = damage::expected_damage(mob.damage, template.accuracy, PLAYER_DEFENSE)
+        / f64::from(template.attack_period.as_secs());
+    let fight_secs = f64::from(mob.hp) / player_dps;
+    fight_secs * mob_dps / f64::from(player.hp.max(1))
+}
+
+/// Hasted players and mobs attack twice as often, but never more than once a tick.
+fn attack_period(period: TickDuration, effects: &StatusEffects, now: Tick) -> TickDuration {
+    let period = if effects.has(StatusKind::Haste, now) {
+        TickDuration::from_secs(period.as_secs() / 2.0)
+    } else {
+        period
+    };
+    period.max(TickDuration::one())
+}
+
 pub fn attack_with_player(
     player: &Player,
     mob: &mut Mob,
+    now: Tick,
+    rng: &mut StdRng,
     players: &PlayerColl,
     writer: &mut EventWriter,
 ) -> bool {
     let room_id = player.room_id;
-    let damage = 10;
+    let outcome = damage::roll_attack(PLAYER_ACCURACY, mob.template.defense, rng);
+    if outcome == HitOutcome::Miss {
+        let msg_self = format!("You miss the {}.", mob.template.name);
+        writer.tell(player.id, span(&msg_self).color(Color::LightCyan).line());
+        let msg_others = format!("{} misses the {}.", player.name, mob.template.name);
+        writer.tell_many(
+            players.ids_in_room_except(room_id, player.id),
+            span(&msg_others).color(Color::Cyan).line(),
+        );
+        return false;
+    }
+    let base = PLAYER_DAMAGE + player.status_effects.potency(StatusKind::Blessing, now);
+    let damage = mob
+        .template
+        .damage_modifiers
+        .apply(damage::roll_damage(base, outcome, rng), PLAYER_DAMAGE_TYPE);
+    let (verb_self, verb_others) = damage::hit_verbs(damage, mob.max_hp);
 
-    let msg_self = format!("You hit the {} for {} damage.", mob.template.name, damage);
+    let msg_self = format!(
+        "{}You {} the {} for {} {} damage.",
+        critical_prefix(outcome),
+        verb_self,
+        mob.template.name,
+        damage,
+        PLAYER_DAMAGE_TYPE
+    );
     writer.tell(player.id, span(&msg_self).color(Color::LightCyan).line());
     let msg_others = format!(
-        "{} hits the {} for {} damage.",
-        player.name, mob.template.name, damage
+        "{}{} {} the {} for {} {} damage.",
+        critical_prefix(outcome),
+        player.name,
+        verb_others,
+        mob.template.name,
+        damage,
+        PLAYER_DAMAGE_TYPE
     );
     writer.tell_many(
         players.ids_in_room_except(room_id, player.id),
@@ -171,60 +506,346 @@ pub fn attack_with_player(
             span(&msg).color(Color::DarkGrey).line(),
         );
     } else {
-        mob.hp -= 10;
+        mob.hp -= damage;
     }
     killed
 }
 
-pub fn tick_mob_attacks(writer: &mut EventWriter, state: &mut GameState) {
-    let GameState { ticks, players, mobs, .. } = state;
-    let mut killed_players = vec![];
+fn attack_player_with_player(
+    attacker_id: Id<Player>,
+    target_id: Id<Player>,
+    now: Tick,
+    rng: &mut StdRng,
+    players: &mut PlayerColl,
+    writer: &mut EventWriter,
+) -> bool {
+    let (Some(attacker), Some(target)) = (
+        players.by_id().get(&attacker_id),
+        players.by_id().get(&target_id),
+    ) else {
+        return false;
+    };
+    let room_id = attacker.room_id;
+    let observer_ids = players
+        .ids_in_room_except(room_id, attacker_id)
+        .filter(|id| *id != target_id)
+        .collect::<Vec<_>>();
+
+    let outcome = damage::roll_attack(PLAYER_ACCURACY, PLAYER_DEFENSE, rng);
+    if outcome == HitOutcome::Miss {
+        let msg_self = format!("You miss {}.", target.name);
+        writer.tell(attacker_id, span(&msg_self).color(Color::LightCyan).line());
+        let msg_target = format!("{} misses you.", attacker.name);
+        writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+        let msg_others = format!("{} misses {}.", attacker.name, target.name);
+        writer.tell_many(
+            observer_ids.into_iter(),
+            span(&msg_others).color(Color::Cyan).line(),
+        );
+        return false;
+    }
+    let base = PLAYER_DAMAGE + attacker.status_effects.potency(StatusKind::Blessing, now);
+    let damage = damage::roll_damage(base, outcome, rng);
+    let (verb_self, verb_others) = damage::hit_verbs(damage, target.max_hp);
+    let critical = critical_prefix(outcome);
 
-    let mobs_on_this_tick = mobs
+    let msg_self = format!(
+        "{}You {} {} for {} {} damage.",
+        critical, verb_self, target.name, damage, PLAYER_DAMAGE_TYPE
+    );
+    writer.tell(attacker_id, span(&msg_self).color(Color::LightCyan).line());
+    let msg_target = format!(
+        "{}{} {} you for {} {} damage.",
+        critical, attacker.name, verb_others, damage, PLAYER_DAMAGE_TYPE
+    );
+    writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+    let msg_others = format!(
+        "{}{} {} {} for {} {} damage.",
+        critical, attacker.name, verb_others, target.name, damage, PLAYER_DAMAGE_TYPE
+    );
+    writer.tell_many(
+        observer_ids.into_iter(),
+        span(&msg_others).color(Color::Cyan).line(),
+    );
+
+    damage_player(target_id, damage, players, writer)
+}
+
+pub fn tick_mob_attacks(writer: &mut EventWriter, state: &mut GameState) {
+    let mobs_on_this_tick = state
+        .mobs
         .by_id()
         .values()
-        .filter(|mob| ticks.is_on_division(mob.template.attack_period, mob.attack_offset))
+        .filter(|mob| {
+            let period =
+                attack_period(mob.template.attack_period, &mob.status_effects, state.ticks);
+            state.ticks.is_on_division(period, mob.attack_offset) && !mob.is_stunned(state.ticks)
+        })
         .map(|mob| mob.id)
         .collect::<Vec<_>>();
 
     for mob_id in mobs_on_this_tick {
+        if flee_mob(mob_id, writer, state) {
+            continue;
+        }
+        pursue_target(mob_id, writer, state);
+
+        let GameState { ticks, players, mobs, rng, .. } = state;
         mobs.modify(&mob_id, |mob| {
-            update_mob_target(mob, players, writer);
+            update_mob_target(mob, rng, players, writer);
         });
         let mob = mobs.by_id().get(&mob_id).unwrap_or_else(|| unreachable!());
 
         if let Some(target_id) = mob.attack_target {
-            let killed = attack_with_mob(mob, target_id, players, writer);
-            if killed {
-                let respawn_at = Id::new(0);
-                killed_players.push((target_id, respawn_at));
-
-                players.modify(&target_id, |p| p.room_id = respawn_at);
-
-                let mob_ids_in_room =
-                    mobs.by_room_id().get(&mob.room_id).cloned().unwrap_or_default();
-                for mob_id in mob_ids_in_room {
-                    mobs.modify(&mob_id, |mob| {
-                        if mob.hostile_to.remove(&target_id) && mob.attack_target == Some(target_id)
-                        {
-                            mob.attack_target = None;
-                        }
-                    });
+            if attack_with_mob(mob, target_id, *ticks, rng, players, writer) {
+                respawn_player(target_id, writer, state);
+            } else if is_wimpy(target_id, state) {
+                writer.tell(target_id, span("You panic!").color(Color::LightRed).line());
+                if let Err(err) = flee(target_id, writer, state) {
+                    warn!("Wimpy: {}", err);
                 }
             }
         }
     }
+}
+
+/// Runs the player out of the room through a random open exit.
+pub fn flee(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("flee: Self not found")?;
+    let room = state.rooms.get(&player.room_id).ok_or("flee: Room not found")?;
+    let exits = game_room::open_exits(room, state)
+        .map(|(direction, to)| (direction.to_string(), to))
+        .collect::<Vec<_>>();
+    let Some((direction, to_room_id)) = exits.choose(&mut state.rng).cloned() else {
+        writer.tell(player_id, Line::str("There is nowhere to flee!"));
+        return Ok(());
+    };
+    flee_through(player_id, &direction, to_room_id, writer, state)
+}
+
+/// Runs the player out of the room through the given exit. While in combat, they may stumble
+/// instead and be stunned for a moment.
+pub fn flee_through(
+    player_id: Id<Player>,
+    direction: &str,
+    to_room_id: Id<Room>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("flee: Self not found")?;
+    let (room_id, player_name) = (player.room_id, player.name.clone());
+
+    if is_in_combat(player, state) && state.rng.gen_bool(FLEE_FAILURE_CHANCE) {
+        let stumble = StatusApplication {
+            status: StatusKind::Stun,
+            secs: FLEE_STUMBLE_SECS,
+            potency: 0,
+        };
+        let now = state.ticks;
+        state.players.modify(&player_id, |p| p.status_effects.apply(&stumble, now));
+        let msg_self = "You try to flee, but stumble!";
+        writer.tell(player_id, span(msg_self).color(Color::LightRed).line());
+        let msg_others = format!("{} tries to flee, but stumbles.", player_name);
+        writer.tell_many(
+            state.players.ids_in_room_except(room_id, player_id),
+            span(&msg_others).color(Color::Red).line(),
+        );
+        return Ok(());
+    }
 
-    let GameState { rooms, .. } = &*state;
-    killed_players.into_iter().for_each(|(player_id, respawn_room_id)| {
-        if let Some(room) = rooms.get(&respawn_room_id) {
-            // TODO: tell other players
-            describe_room(player_id, room, writer, state);
+    let msg_self = format!("You flee {}!", direction);
+    writer.tell(player_id, span(&msg_self).color(Color::LightCyan).line());
+    state.players.modify(&player_id, |p| {
+        p.attack_target = None;
+        p.travel_path.clear();
+    });
+    game_logic::move_self(player_id, to_room_id, direction, writer, state)
+}
+
+/// Whether the player is fighting, or a mob or player in their room is fighting them.
+pub fn is_in_combat(player: &Player, state: &GameState) -> bool {
+    let is_hostile_mob = |mob_id: &Id<Mob>| {
+        state
+            .mobs
+            .by_id()
+            .get(mob_id)
+            .is_some_and(|mob| mob.hostile_to.contains(&player.id))
+    };
+    let is_attacking_player = |other: &Player| {
+        other.room_id == player.room_id
+            && other.attack_target == Some(AttackTarget::Player(player.id))
+    };
+    player.attack_target.is_some()
+        || state
+            .mobs
+            .by_room_id()
+            .get(&player.room_id)
+            .into_iter()
+            .flatten()
+            .any(is_hostile_mob)
+        || state.players.by_id().values().any(is_attacking_player)
+}
+
+/// Whether the player's HP dropped below their wimpy setting, and they are able to flee.
+fn is_wimpy(player_id: Id<Player>, state: &GameState) -> bool {
+    state.players.by_id().get(&player_id).is_some_and(|player| {
+        player.wimpy.is_some_and(|wimpy| player.hp < wimpy) && !player.is_stunned(state.ticks)
+    })
+}
+
+pub fn wimpy(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("wimpy: Self not found")?;
+    let max_hp = player.max_hp;
+
+    let wimpy = match words.as_slice() {
+        [] => {
+            let msg = match player.wimpy {
+                Some(wimpy) => {
+                    format!("You flee automatically when your HP drops below {}.", wimpy)
+                }
+                None => "You do not flee automatically.".to_string(),
+            };
+            writer.tell(player_id, Line::str(&msg));
+            return Ok(());
+        }
+        ["off"] => None,
+        [hp] => match hp.parse::<i32>() {
+            Ok(hp) if hp > 0 && hp < max_hp => Some(hp),
+            _ => {
+                let msg = format!("Wimpy must be a number from 1 to {}, or off.", max_hp - 1);
+                writer.tell(player_id, Line::str(&msg));
+                return Ok(());
+            }
+        },
+        _ => {
+            writer.tell(player_id, Line::str("Usage: wimpy [hp|off]"));
+            return Ok(());
         }
+    };
+
+    let msg = match wimpy {
+        Some(wimpy) => format!(
+            "You will flee automatically when your HP drops below {}.",
+            wimpy
+        ),
+        None => "You will no longer flee automatically.".to_string(),
+    };
+    writer.tell(player_id, Line::str(&msg));
+    state.players.modify(&player_id, |player| player.wimpy = wimpy);
+    if let Some(player) = state.players.by_id().get(&player_id) {
+        character::save_player(player, writer);
+    }
+    Ok(())
+}
+
+/// Runs a badly hurt mob out of the fight through a random open exit.
+fn flee_mob(mob_id: Id<Mob>, writer: &mut EventWriter, state: &mut GameState) -> bool {
+    let Some(mob) = state.mobs.by_id().get(&mob_id).filter(|mob| mob.should_flee()) else {
+        return false;
+    };
+    let (room_id, mob_name) = (mob.room_id, mob.template.name.clone());
+    let Some(room) = state.rooms.get(&room_id) else {
+        return false;
+    };
+    let exits = game_room::open_exits(room, state)
+        .filter(|(_, to)| !game_zone::is_safe_room(*to, state))
+        .map(|(direction, to)| (direction.to_string(), to))
+        .collect::<Vec<_>>();
+    let Some((direction, to_room_id)) = exits.choose(&mut state.rng).cloned() else {
+        return false;
+    };
+
+    let msg = format!("The {} flees {}.", mob_name, direction);
+    writer.tell_many(
+        state.players.ids_in_room(room_id),
+        span(&msg).color(Color::Cyan).line(),
+    );
+    move_mob(mob_id, to_room_id, writer, state);
+    state.mobs.modify(&mob_id, |mob| mob.attack_target = None);
+    true
+}
+
+/// Chases a player the mob is hostile to into a neighbouring room when none of them are left in
+/// its own room, unless the mob has already chased through as many rooms as it is willing to.
+/// Mobs hurt badly enough to flee do not give chase, or they would run straight back.
+fn pursue_target(mob_id: Id<Mob>, writer: &mut EventWriter, state: &mut GameState) {
+    let Some(mob) = state.mobs.by_id().get(&mob_id).filter(|mob| !mob.is_badly_hurt()) else {
+        return;
+    };
+    let Some(room) = state.rooms.get(&mob.room_id) else {
+        return;
+    };
+    let hostile_players = mob
+        .attack_target
+        .into_iter()
+        .chain(mob.hostile_to.iter().copied())
+        .filter_map(|player_id| state.players.by_id().get(&player_id))
+        .collect::<Vec<_>>();
+    if mob.pursued_rooms >= mob.template.pursuit_range
+        || hostile_players.iter().any(|player| player.room_id == mob.room_id)
+    {
+        return;
+    }
+    let Some((direction, target)) = hostile_players.iter().find_map(|player| {
+        game_room::open_exits(room, state)
+            .find(|(_, to)| *to == player.room_id && !game_zone::is_safe_room(*to, state))
+            .map(|(direction, _)| (direction, player))
+    }) else {
+        return;
+    };
+
+    let msg = format!(
+        "The {} chases {} {}.",
+        mob.template.name, target.name, direction
+    );
+    writer.tell_many(
+        state.players.ids_in_room(mob.room_id),
+        span(&msg).color(Color::Red).line(),
+    );
+    let (target_id, to_room_id) = (target.id, target.room_id);
+    move_mob(mob_id, to_room_id, writer, state);
+    state.mobs.modify(&mob_id, |mob| {
+        mob.attack_target = Some(target_id);
+        mob.pursued_rooms += 1;
     });
 }
 
-fn update_mob_target(mob: &mut Mob, players: &PlayerColl, writer: &mut EventWriter) {
+fn move_mob(
+    mob_id: Id<Mob>,
+    to_room_id: Id<Room>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) {
+    let (from_room_id, mob_name) = state.mobs.modify(&mob_id, |mob| {
+        let from_room_id = mob.room_id;
+        mob.room_id = to_room_id;
+        (from_room_id, mob.template.name.clone())
+    });
+    let msg = state
+        .rooms
+        .get(&to_room_id)
+        .and_then(|to_room| to_room.exit_direction_to(from_room_id))
+        .map_or(format!("A {} appears.", mob_name), |direction| {
+            format!("A {} arrives from {}.", mob_name, direction)
+        });
+    writer.tell_many(state.players.ids_in_room(to_room_id), Line::str(&msg));
+}
+
+fn update_mob_target(
+    mob: &mut Mob,
+    rng: &mut StdRng,
+    players: &PlayerColl,
+    writer: &mut EventWriter,
+) {
     mob.hostile_to.retain(|player_id| players.by_id().contains_key(player_id));
 
     if let Some(target_id) = mob.attack_target {
@@ -243,10 +864,11 @@ fn update_mob_target(mob: &mut Mob, players: &PlayerColl, writer: &mut EventWrit
             .collect::<Vec<_>>();
         let new_target = match potential_targets.len() {
             0 => None,
-            len => Some(potential_targets[thread_rng().gen_range(0..len)]),
+            len => Some(potential_targets[rng.gen_range(0..len)]),
         };
         if let Some(new_target) = new_target {
             mob.attack_target = Some(new_target.id);
+            mob.pursued_rooms = 0;
 
             let msg_target = format!("The {} attacks you.", mob.template.name);
             writer.tell(
@@ -265,53 +887,134 @@ fn update_mob_target(mob: &mut Mob, players: &PlayerColl, writer: &mut EventWrit
 fn attack_with_mob(
     mob: &Mob,
     target_id: Id<Player>,
+    now: Tick,
+    rng: &mut StdRng,
     players: &mut PlayerColl,
     writer: &mut EventWriter,
 ) -> bool {
     let mob_name = &mob.template.name;
-    let damage = mob.template.damage;
+    let Some(target) = players.by_id().get(&target_id) else {
+        return false;
+    };
 
-    let (target_name, killed) = players.modify(&target_id, |target| {
-        let killed = damage >= target.hp;
-        if killed {
-            target.hp = target.max_hp;
-            target.attack_target = None;
-        } else {
-            target.hp -= damage;
-        }
-        (target.name.clone(), killed)
-    });
+    let outcome = damage::roll_attack(mob.template.accuracy, PLAYER_DEFENSE, rng);
+    if outcome == HitOutcome::Miss {
+        let msg_target = format!("The {} misses you.", mob_name);
+        writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
+        let msg_others = format!("The {} misses {}.", mob_name, target.name);
+        writer.tell_many(
+            players.ids_in_room_except(mob.room_id, target_id),
+            span(&msg_others).color(Color::Red).line(),
+        );
+        return false;
+    }
+    let base = mob.damage + mob.status_effects.potency(StatusKind::Blessing, now);
+    let damage = damage::roll_damage(base, outcome, rng);
+    let damage_type = mob.template.damage_type;
+    let (_, verb) = damage::hit_verbs(damage, target.max_hp);
 
-    let msg_target = format!("The {} hits you for {} damage.", mob_name, damage);
+    let msg_target = format!(
+        "{}The {} {} you for {} {} damage.",
+        critical_prefix(outcome),
+        mob_name,
+        verb,
+        damage,
+        damage_type
+    );
     writer.tell(target_id, span(&msg_target).color(Color::LightRed).line());
     let msg_others = format!(
-        "The {} hits {} for {} damage.",
-        mob_name, target_name, damage
+        "{}The {} {} {} for {} {} damage.",
+        critical_prefix(outcome),
+        mob_name,
+        verb,
+        target.name,
+        damage,
+        damage_type
     );
     writer.tell_many(
         players.ids_in_room_except(mob.room_id, target_id),
         span(&msg_others).color(Color::Red).line(),
     );
 
+    damage_player(target_id, damage, players, writer)
+}
+
+fn critical_prefix(outcome: HitOutcome) -> &'static str {
+    if outcome == HitOutcome::Critical {
+        "Critical hit! "
+    } else {
+        ""
+    }
+}
+
+/// Takes HP from a player, telling the room if it kills them. The caller is expected to respawn
+/// a killed player.
+pub fn damage_player(
+    player_id: Id<Player>,
+    damage: i32,
+    players: &mut PlayerColl,
+    writer: &mut EventWriter,
+) -> bool {
+    let (name, room_id, killed) = players.modify(&player_id, |player| {
+        let killed = damage >= player.hp;
+        if killed {
+            player.hp = player.max_hp;
+            player.attack_target = None;
+        } else {
+            player.hp -= damage;
+        }
+        (player.name.clone(), player.room_id, killed)
+    });
+
     if killed {
         let msg_target = "You die.";
-        writer.tell(target_id, span(msg_target).color(Color::DarkGrey).line());
-        let msg_others = format!("{} dies.", target_name);
+        writer.tell(player_id, span(msg_target).color(Color::DarkGrey).line());
+        let msg_others = format!("{} dies.", name);
         writer.tell_many(
-            players.ids_in_room_except(mob.room_id, target_id),
+            players.ids_in_room_except(room_id, player_id),
             span(&msg_others).color(Color::DarkGrey).line(),
         );
     }
     killed
 }
 
+/// Brings a killed player back to life in the respawn room of the zone they died in. The mobs
+/// they were fighting forget about them.
+pub fn respawn_player(player_id: Id<Player>, writer: &mut EventWriter, state: &mut GameState) {
+    let Some(player) = state.players.by_id().get(&player_id) else {
+        return;
+    };
+    let room_id = player.room_id;
+    let respawn_room_id = game_zone::respawn_room_id(room_id, state);
+
+    let mob_ids_in_room = state.mobs.by_room_id().get(&room_id).cloned().unwrap_or_default();
+    for mob_id in mob_ids_in_room {
+        state.mobs.modify(&mob_id, |mob| {
+            if mob.hostile_to.remove(&player_id) && mob.attack_target == Some(player_id) {
+                mob.attack_target = None;
+            }
+        });
+    }
+    state.players.modify(&player_id, |p| {
+        p.room_id = respawn_room_id;
+        p.status_effects.clear();
+    });
+    arrive_in_room(player_id, respawn_room_id, writer, state);
+}
+
 pub fn tick_heal_players(state: &mut GameState) {
     if state.ticks.is_on_division(*PLAYER_HEAL_FREQ, TickDuration::zero()) {
         let players_in_combat = state
             .mobs
             .by_id()
             .values()
-            .flat_map(|mob| mob.hostile_to.iter())
+            .flat_map(|mob| mob.hostile_to.iter().copied())
+            .chain(
+                state.players.by_id().values().flat_map(|player| match player.attack_target {
+                    Some(AttackTarget::Player(target_id)) => vec![player.id, target_id],
+                    _ => vec![],
+                }),
+            )
             .collect::<HashSet<_>>();
 
         let healed_player_ids = state
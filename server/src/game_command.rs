@@ -0,0 +1,330 @@
+use once_cell::sync::Lazy;
+
+use crate::{
+    account::Role,
+    event_writer::EventWriter,
+    game_admin, game_alias, game_build,
+    game_chat::{self, ChatCommand},
+    game_combat, game_consider, game_help, game_logic, game_map, game_queue, game_skill,
+    game_state::GameState,
+    game_travel, game_zone,
+    id::Id,
+    line::Line,
+    player::Player,
+    text_util::edit_distance,
+};
+
+pub type Handler =
+    fn(Id<Player>, Vec<&str>, &mut EventWriter, &mut GameState) -> Result<(), String>;
+
+pub enum Args {
+    None,
+    Optional,
+    Required,
+}
+
+pub struct Command {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub syntax: &'static str,
+    pub args: Args,
+    pub help: &'static str,
+    pub role: Role,
+    pub handler: Handler,
+}
+
+impl Command {
+    pub fn usage(&self) -> String {
+        if self.syntax.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{} {}", self.name, self.syntax)
+        }
+    }
+
+    pub fn run(
+        &self,
+        player_id: Id<Player>,
+        words: Vec<&str>,
+        writer: &mut EventWriter,
+        state: &mut GameState,
+    ) -> Result<(), String> {
+        let args_ok = match self.args {
+            Args::None => words.is_empty(),
+            Args::Optional => true,
+            Args::Required => !words.is_empty(),
+        };
+        if args_ok {
+            (self.handler)(player_id, words, writer, state)
+        } else {
+            writer.tell(player_id, Line::str(&format!("Usage: {}", self.usage())));
+            Ok(())
+        }
+    }
+}
+
+#[rustfmt::skip]
+pub static COMMANDS: Lazy<Vec<Command>> = Lazy::new(|| vec![
+    Command {
+        name: "look", aliases: &["l"], syntax: "[target]", args: Args::Optional,
+        help: "Look around or at something", role: Role::Player,
+        handler: game_logic::look,
+    },
+    Command {
+        name: "consider", aliases: &["con"], syntax: "<target>", args: Args::Required,
+        help: "Estimate how hard a fight against something would be", role: Role::Player,
+        handler: game_consider::consider,
+    },
+    Command {
+        name: "kill", aliases: &[], syntax: "<target>", args: Args::Required,
+        help: "Attack something or someone", role: Role::Player,
+        handler: game_combat::kill,
+    },
+    Command {
+        name: "pvp", aliases: &[], syntax: "[on|off]", args: Args::Optional,
+        help: "Opt in to or out of fighting other players. Skills only work on mobs",
+        role: Role::Player,
+        handler: game_combat::pvp,
+    },
+    Command {
+        name: "flee", aliases: &[], syntax: "", args: Args::None,
+        help: "Run from a fight through a random exit", role: Role::Player,
+        handler: |player_id, _, writer, state| game_combat::flee(player_id, writer, state),
+    },
+    Command {
+        name: "wimpy", aliases: &[], syntax: "[hp|off]", args: Args::Optional,
+        help: "Flee automatically when your HP drops below a threshold", role: Role::Player,
+        handler: game_combat::wimpy,
+    },
+    Command {
+        name: "say", aliases: &["\""], syntax: "<message>", args: Args::Required,
+        help: "Say something to the others in the room", role: Role::Player,
+        handler: |player_id, words, writer, state| {
+            game_chat::chat(player_id, words, ChatCommand::Say, writer, state)
+        },
+    },
+    Command {
+        name: "emote", aliases: &[":"], syntax: "<action>", args: Args::Required,
+        help: "Act out something", role: Role::Player,
+        handler: |player_id, words, writer, state| {
+            game_chat::chat(player_id, words, ChatCommand::Emote, writer, state)
+        },
+    },
+    Command {
+        name: "roll", aliases: &[], syntax: "", args: Args::None,
+        help: "Roll a die", role: Role::Player,
+        handler: |player_id, _, writer, state| game_logic::roll_die(player_id, writer, state),
+    },
+    Command {
+        name: "who", aliases: &[], syntax: "", args: Args::None,
+        help: "See who is online", role: Role::Player,
+        handler: |player_id, _, writer, state| {
+            game_logic::list_players(player_id, writer, state);
+            Ok(())
+        },
+    },
+    Command {
+        name: "time", aliases: &[], syntax: "", args: Args::None,
+        help: "See what time of day it is", role: Role::Player,
+        handler: |player_id, _, writer, state| {
+            game_logic::time(player_id, writer, state);
+            Ok(())
+        },
+    },
+    Command {
+        name: "score", aliases: &[], syntax: "", args: Args::None,
+        help: "See how you are doing", role: Role::Player,
+        handler: |player_id, _, writer, state| game_logic::score(player_id, writer, state),
+    },
+    Command {
+        name: "skills", aliases: &[], syntax: "", args: Args::None,
+        help: "List your skills and mana", role: Role::Player,
+        handler: |player_id, _, writer, state| game_skill::skills(player_id, writer, state),
+    },
+    Command {
+        name: "ignore", aliases: &[], syntax: "[player]", args: Args::Optional,
+        help: "Ignore someone's chat, or list who you ignore", role: Role::Player,
+        handler: game_chat::ignore,
+    },
+    Command {
+        name: "unignore", aliases: &[], syntax: "<player>", args: Args::Required,
+        help: "Stop ignoring someone", role: Role::Player,
+        handler: game_chat::unignore,
+    },
+    Command {
+        name: "alias", aliases: &[], syntax: "[name [commands]]", args: Args::Optional,
+        help: "List aliases, or define your own, e.g. alias k kill $1; say Die, $1!",
+        role: Role::Player,
+        handler: game_alias::alias,
+    },
+    Command {
+        name: "unalias", aliases: &[], syntax: "<name>", args: Args::Required,
+        help: "Delete one of your aliases", role: Role::Player,
+        handler: game_alias::unalias,
+    },
+    Command {
+        name: "help", aliases: &[], syntax: "", args: Args::None,
+        help: "You're looking at it", role: Role::Player,
+        handler: |player_id, _, writer, state| game_help::help(player_id, writer, state),
+    },
+    Command {
+        name: "zone", aliases: &[], syntax: "", args: Args::None,
+        help: "Find out about the area you are in", role: Role::Player,
+        handler: |player_id, _, writer, state| game_zone::zone(player_id, writer, state),
+    },
+    Command {
+        name: "map", aliases: &[], syntax: "[radius]", args: Args::Optional,
+        help: "Draw a map of the surrounding area", role: Role::Player,
+        handler: game_map::map,
+    },
+    Command {
+        name: "travel", aliases: &[], syntax: "<room name>", args: Args::Required,
+        help: "Walk to a room you have been to before", role: Role::Player,
+        handler: game_travel::travel,
+    },
+    Command {
+        name: "stop", aliases: &[], syntax: "", args: Args::None,
+        help: "Drop your queued commands and stop travelling", role: Role::Player,
+        handler: |player_id, _, writer, state| {
+            game_queue::stop(player_id, writer, state);
+            Ok(())
+        },
+    },
+    Command {
+        name: "goto", aliases: &[], syntax: "<room>", args: Args::Required,
+        help: "Teleport to a room by id or name", role: Role::Builder,
+        handler: game_admin::goto,
+    },
+    Command {
+        name: "setvar", aliases: &[], syntax: "<room> <var> <value>", args: Args::Required,
+        help: "Set a room variable", role: Role::Builder,
+        handler: game_admin::set_var,
+    },
+    Command {
+        name: "spawn", aliases: &[], syntax: "<template>", args: Args::Required,
+        help: "Spawn a mob in this room", role: Role::Builder,
+        handler: game_admin::spawn,
+    },
+    Command {
+        name: "rcreate", aliases: &[], syntax: "[name]", args: Args::Optional,
+        help: "Create a room without exits in this zone", role: Role::Builder,
+        handler: game_build::create_room,
+    },
+    Command {
+        name: "dig", aliases: &[], syntax: "<direction> [room id or name]", args: Args::Required,
+        help: "Create an exit and its way back, digging a new room if needed", role: Role::Builder,
+        handler: game_build::dig,
+    },
+    Command {
+        name: "redit", aliases: &[], syntax: "name|desc|object|spawn|exit ...", args: Args::Required,
+        help: "Edit this room", role: Role::Builder,
+        handler: game_build::edit_room,
+    },
+    Command {
+        name: "zreset", aliases: &[], syntax: "", args: Args::None,
+        help: "Reset the zone you are in", role: Role::Builder,
+        handler: |player_id, _, writer, state| game_zone::reset_zone_command(player_id, writer, state),
+    },
+    Command {
+        name: "rsave", aliases: &[], syntax: "", args: Args::None,
+        help: "Save all rooms to disk", role: Role::Builder,
+        handler: |player_id, _, writer, state| game_build::save_rooms(player_id, writer, state),
+    },
+    Command {
+        name: "summon", aliases: &[], syntax: "<player>", args: Args::Required,
+        help: "Bring a player to you", role: Role::Admin,
+        handler: game_admin::summon,
+    },
+    Command {
+        name: "kick", aliases: &[], syntax: "<player>", args: Args::Required,
+        help: "Disconnect a player", role: Role::Admin,
+        handler: game_admin::kick,
+    },
+    Command {
+        name: "ban", aliases: &[], syntax: "<player>", args: Args::Required,
+        help: "Disconnect a player and keep them out", role: Role::Admin,
+        handler: game_admin::ban,
+    },
+    Command {
+        name: "unban", aliases: &[], syntax: "<player>", args: Args::Required,
+        help: "Lift a ban", role: Role::Admin,
+        handler: game_admin::unban,
+    },
+    Command {
+        name: "mute", aliases: &[], syntax: "<player> [duration]", args: Args::Required,
+        help: "Stop a player from chatting, e.g. for 10m", role: Role::Admin,
+        handler: game_chat::mute,
+    },
+    Command {
+        name: "unmute", aliases: &[], syntax: "<player>", args: Args::Required,
+        help: "Let a muted player chat again", role: Role::Admin,
+        handler: game_chat::unmute,
+    },
+    Command {
+        name: "slay", aliases: &[], syntax: "<target>", args: Args::Required,
+        help: "Kill a mob instantly", role: Role::Admin,
+        handler: game_admin::slay,
+    },
+    Command {
+        name: "restore", aliases: &[], syntax: "[player]", args: Args::Optional,
+        help: "Fully heal yourself or a player", role: Role::Admin,
+        handler: game_admin::restore,
+    },
+    Command {
+        name: "announce", aliases: &[], syntax: "<message>", args: Args::Required,
+        help: "Send a message to everyone online", role: Role::Admin,
+        handler: game_admin::announce,
+    },
+]);
+
+pub fn available(role: Role) -> impl Iterator<Item = &'static Command> {
+    COMMANDS.iter().filter(move |command| command.role <= role)
+}
+
+pub fn find(name: &str, role: Role) -> Option<&'static Command> {
+    available(role).find(|command| command.name == name)
+}
+
+pub fn resolve_alias(word: &str, role: Role) -> Option<&'static str> {
+    available(role)
+        .find(|command| command.aliases.contains(&word))
+        .map(|command| command.name)
+}
+
+pub enum Completion {
+    Unique(String),
+    Ambiguous(Vec<String>),
+    Unknown { suggestions: Vec<String> },
+}
+
+/// Completes a possibly abbreviated command word against everything the player could type,
+/// falling back to suggestions of similarly spelled candidates.
+pub fn complete(word: &str, mut candidates: Vec<&str>) -> Completion {
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    if candidates.contains(&word) {
+        return Completion::Unique(word.to_string());
+    }
+    let prefixed = candidates
+        .iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .map(|candidate| candidate.to_string())
+        .collect::<Vec<_>>();
+    match prefixed.len() {
+        0 => {
+            let max_distance = (word.chars().count() / 3).clamp(1, 2);
+            let mut close = candidates
+                .into_iter()
+                .map(|candidate| (edit_distance(word, candidate), candidate))
+                .filter(|(distance, _)| *distance <= max_distance)
+                .collect::<Vec<_>>();
+            close.sort_unstable();
+            let suggestions =
+                close.into_iter().take(3).map(|(_, candidate)| candidate.to_string()).collect();
+            Completion::Unknown { suggestions }
+        }
+        1 => Completion::Unique(prefixed.into_iter().next().unwrap_or_default()),
+        _ => Completion::Ambiguous(prefixed),
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    damage::DamageType,
+    event_writer::EventWriter,
+    game_combat,
+    game_room::{resolve_targets_in_room, RoomTarget},
+    game_state::GameState,
+    id::Id,
+    line::{span, Color, Line, LineSpan},
+    mob::Mob,
+    player::Player,
+    text_util::and_spans,
+};
+
+/// How dangerous a mob is to a player, from the HP they would lose fighting it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Danger {
+    Trivial,
+    Easy,
+    Even,
+    Hard,
+    Deadly,
+}
+
+impl Danger {
+    pub fn of(mob: &Mob, player: &Player) -> Danger {
+        let hp_loss = game_combat::expected_hp_loss(player, mob);
+        if hp_loss < 0.15 {
+            Danger::Trivial
+        } else if hp_loss < 0.4 {
+            Danger::Easy
+        } else if hp_loss < 0.75 {
+            Danger::Even
+        } else if hp_loss < 1.25 {
+            Danger::Hard
+        } else {
+            Danger::Deadly
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Danger::Trivial => Color::DarkGrey,
+            Danger::Easy => Color::Green,
+            Danger::Even => Color::Yellow,
+            Danger::Hard => Color::Orange,
+            Danger::Deadly => Color::Red,
+        }
+    }
+
+    fn assessment(&self, mob_name: &str) -> String {
+        match self {
+            Danger::Trivial => format!("You could defeat the {} in your sleep.", mob_name),
+            Danger::Easy => format!("The {} should not give you much trouble.", mob_name),
+            Danger::Even => format!("The {} looks like a fair fight.", mob_name),
+            Danger::Hard => format!("You might barely survive a fight with the {}.", mob_name),
+            Danger::Deadly => format!("The {} would make short work of you.", mob_name),
+        }
+    }
+}
+
+pub fn consider(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("consider: Self not found")?;
+    let room = state.rooms.get(&player.room_id).ok_or("consider: Room not found")?;
+
+    let mob = match resolve_targets_in_room(&words.join(" "), room, &state.mobs).into_iter().next()
+    {
+        Some(RoomTarget::Mob { mob }) => mob,
+        Some(_) => {
+            writer.tell(
+                player_id,
+                Line::str("That is not something you could fight."),
+            );
+            return Ok(());
+        }
+        None => {
+            writer.tell(player_id, Line::str("You do not see that here."));
+            return Ok(());
+        }
+    };
+
+    let danger = Danger::of(mob, player);
+    let mut lines = vec![span(&danger.assessment(&mob.template.name)).color(danger.color()).line()];
+    let modifiers = &mob.template.damage_modifiers;
+    if !modifiers.resistances.is_empty() {
+        let types = and_spans(damage_type_spans(&modifiers.resistances));
+        lines.push(Line::str("It resists ").extend(types).push(span(" damage.")));
+    }
+    if !modifiers.vulnerabilities.is_empty() {
+        let types = and_spans(damage_type_spans(&modifiers.vulnerabilities));
+        lines.push(Line::str("It is vulnerable to ").extend(types).push(span(" damage.")));
+    }
+    writer.tell_lines(player_id, &lines);
+    Ok(())
+}
+
+fn damage_type_spans(types: &[DamageType]) -> Vec<LineSpan> {
+    types.iter().map(|damage_type| span(&damage_type.to_string()).bold()).collect()
+}
@@ -1,27 +1,60 @@
-use once_cell::sync::Lazy;
-
 use crate::{
+    account::Role,
     event_writer::EventWriter,
+    game_command,
+    game_state::GameState,
     id::Id,
     line::{span, Color, Line},
     player::Player,
 };
 
-#[rustfmt::skip]
-static HELP_LINES: Lazy<Vec<Line>> = Lazy::new(|| vec![
-    span("Commands:").bold().line(),
-    span("look").color(Color::White).line().push(span(" – Look around or at something")),
-    span("north").color(Color::White).line().push(span(", etc. – Move to another room")),
-    span("kill").color(Color::White).line().push(span(" – Attack something or someone")),
-    span("say").color(Color::White).line().push(span(" – Say something to the others in the room")),
-    span("emote").color(Color::White).line().push(span(" – Act out something")),
-    span("roll").color(Color::White).line().push(span(" – Roll a die")),
-    span("who").color(Color::White).line().push(span(" – See who is online")),
-    span("alias").color(Color::White).line().push(span(" – List short aliases for commands")),
-    span("help").color(Color::White).line().push(span(" – You're looking at it")),
-    Line::str("There are also special commands for interacting with specific rooms, or objects in there.",),
-]);
+pub fn help(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &GameState,
+) -> Result<(), String> {
+    let role = state.players.by_id().get(&player_id).ok_or("help: Self not found")?.role;
+
+    let mut lines = vec![span("Commands:").bold().line()];
+    lines.extend(command_lines(Role::Player));
+    lines.push(
+        span("north")
+            .color(Color::White)
+            .line()
+            .push(span(", etc. – Move to another room")),
+    );
+    for extra_role in [Role::Builder, Role::Admin] {
+        if role >= extra_role {
+            let title = format!("Commands for {}s:", extra_role.as_str());
+            lines.push(span(&title).bold().line());
+            lines.extend(command_lines(extra_role));
+        }
+    }
+    lines.push(Line::str(
+        "There are also special commands for interacting with specific rooms, or objects in there.",
+    ));
+
+    writer.tell_lines(player_id, &lines);
+    Ok(())
+}
+
+fn command_lines(role: Role) -> impl Iterator<Item = Line> {
+    game_command::COMMANDS
+        .iter()
+        .filter(move |command| command.role == role)
+        .map(|command| {
+            span(command.name).color(Color::White).line().push(span(&format!(
+                "{} – {}",
+                syntax_suffix(command.syntax),
+                command.help
+            )))
+        })
+}
 
-pub fn help(player_id: Id<Player>, writer: &mut EventWriter) {
-    writer.tell_lines(player_id, &HELP_LINES);
+fn syntax_suffix(syntax: &str) -> String {
+    if syntax.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", syntax)
+    }
 }
@@ -1,42 +1,44 @@
-use std::collections::HashSet;
-
 use crate::{
+    account::Role,
+    character,
     event_writer::EventWriter,
-    game_alias,
-    game_chat::{self, ChatCommand},
-    game_combat, game_help,
+    game_ability, game_alias, game_chat, game_combat,
+    game_command::{self, Completion},
+    game_queue,
     game_room::{
-        describe_room, eval_room_description, resolve_room_specific_command,
-        resolve_target_in_room, run_room_command, RoomSpecificCommand, RoomTarget,
+        arrive_in_room, describe_room, eval_room_description, resolve_room_specific_command,
+        resolve_targets_in_room, room_command_names, run_room_command, RoomSpecificCommand,
+        RoomTarget,
     },
+    game_skill, game_spawn,
     game_state::{GameState, Room},
+    game_status, game_travel, game_zone,
     id::Id,
     line::{span, Color, Line},
-    mob::{Mob, MobTemplate},
     player::Player,
-    text_util::{are, plural},
+    text_util::{are, or_spans, plural},
 };
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 pub fn initialize(state: &mut GameState) {
-    let room_ids_templates = state
-        .rooms
-        .values()
-        .flat_map(|room| {
-            let mob_templates = &state.mob_templates;
-            room.mob_spawns.iter().filter_map(move |spawn| {
-                mob_templates
-                    .get(&spawn.mob_template_id)
-                    .map(|template| (room.id, template.clone()))
-            })
-        })
-        .collect::<Vec<_>>();
-    spawn_mobs(room_ids_templates, state);
+    let room_ids = state.rooms.keys().copied().collect::<Vec<_>>();
+    for room_id in room_ids {
+        game_zone::reset_room(room_id, state);
+    }
+    game_zone::schedule_resets(state);
 }
 
-pub fn on_player_connect(player: Player, writer: &mut EventWriter, state: &mut GameState) {
+pub fn on_player_connect(mut player: Player, writer: &mut EventWriter, state: &mut GameState) {
     let Player { id: player_id, room_id, .. } = player;
 
+    if state.is_banned(&player.name) {
+        writer.tell(player_id, Line::str("You are banned from this server."));
+        writer.disconnect(player_id);
+        return;
+    }
+    player.role = state.account_role(&player.name);
+    player.visited_rooms.insert(room_id);
+
     writer.tell_lines(
         player_id,
         &[
@@ -51,6 +53,12 @@ pub fn on_player_connect(player: Player, writer: &mut EventWriter, state: &mut G
             Line::str(&format_player_count(state.players.by_id().len() + 1)),
         ],
     );
+    if player.role > Role::Player {
+        writer.tell(
+            player_id,
+            Line::str(&format!("You are logged in as {}.", player.role.as_str())),
+        );
+    }
     if let Some(room) = state.rooms.get(&room_id) {
         describe_room(player_id, room, writer, state);
     }
@@ -69,6 +77,7 @@ pub fn on_player_disconnect(
     state: &mut GameState,
 ) {
     if let Some(player) = state.players.remove(&player_id) {
+        character::save_player(&player, writer);
         writer.tell_many(
             state.players.ids_in_room(player.room_id),
             Line::str(&format!("{} disappears.", player.name)),
@@ -78,15 +87,23 @@ pub fn on_player_disconnect(
 
 pub fn on_tick(writer: &mut EventWriter, state: &mut GameState) {
     state.ticks = state.ticks.increase();
+    game_queue::tick_command_queues(writer, state);
+    game_travel::tick_travel(writer, state);
     game_combat::tick_player_attacks(writer, state);
     game_combat::tick_mob_attacks(writer, state);
+    game_ability::tick_mob_abilities(writer, state);
+    game_status::tick_status_effects(writer, state);
     game_combat::tick_heal_players(state);
+    game_skill::tick_mana(state);
     if state.ticks.is_large_tick() {
         on_large_tick(writer, state);
     }
 }
 
 fn on_large_tick(writer: &mut EventWriter, state: &mut GameState) {
+    game_chat::tick_expire_mutes(writer, state);
+    game_zone::tick_ambient_messages(writer, state);
+    game_zone::tick_zone_resets(writer, state);
     {
         let remaining = state.scheduled_room_var_resets.split_off(&(state.ticks.increase()));
         let to_reset = state.scheduled_room_var_resets.clone();
@@ -97,27 +114,7 @@ fn on_large_tick(writer: &mut EventWriter, state: &mut GameState) {
             writer.tell_many(state.players.ids_in_room(*room_id), Line::str(message));
         }
     }
-    {
-        let remaining = state.scheduled_mob_spawns.split_off(&(state.ticks.increase()));
-        let to_respawn = state.scheduled_mob_spawns.clone();
-        state.scheduled_mob_spawns = remaining;
-
-        spawn_mobs(
-            to_respawn
-                .into_values()
-                .filter_map(|(room_id, mob_template_id)| {
-                    state.mob_templates.get(&mob_template_id).map(|template| {
-                        writer.tell_many(
-                            state.players.ids_in_room(room_id),
-                            Line::str(&format!("A {} appears.", template.name)),
-                        );
-                        (room_id, template.clone())
-                    })
-                })
-                .collect(),
-            state,
-        );
-    }
+    game_spawn::tick_respawns(writer, state);
 }
 
 pub fn on_command(
@@ -126,73 +123,103 @@ pub fn on_command(
     writer: &mut EventWriter,
     state: &mut GameState,
 ) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("Self player not found")?;
+
     let mut words: Vec<&str> = command.split_whitespace().collect();
-    let unresolved_head = words.get(0).ok_or("Empty command")?.to_ascii_lowercase();
+    let unresolved_head = words.first().ok_or("Empty command")?.to_ascii_lowercase();
     words[0] = &unresolved_head;
-    words = game_alias::resolve_aliases(words);
+    words = game_alias::resolve_aliases(words, player);
 
-    let command_head = words.remove(0);
+    let unresolved_command = words.remove(0);
     let words = words;
 
-    let player = state.players.by_id().get(&player_id).ok_or("Self player not found")?;
-
-    match command_head {
-        "look" => look(player, words, writer, state),
-        "kill" => game_combat::kill(player.id, words, writer, state),
-        "say" if !words.is_empty() => {
-            game_chat::chat(player, words, ChatCommand::Say, writer, state);
-            Ok(())
+    let command_head = match complete_command_head(unresolved_command, player, state)? {
+        Completion::Unique(command_head) => command_head,
+        Completion::Ambiguous(candidates) => {
+            let candidates = candidates.iter().map(|c| span(c).color(Color::White)).collect();
+            writer.tell(
+                player_id,
+                Line::str("Did you mean ").extend(or_spans(candidates)).push(span("?")),
+            );
+            return Ok(());
         }
-        "emote" if !words.is_empty() => {
-            game_chat::chat(player, words, ChatCommand::Emote, writer, state);
-            Ok(())
+        Completion::Unknown { suggestions } => {
+            unknown_command(player_id, &suggestions, writer);
+            return Ok(());
         }
-        "who" if words.is_empty() => {
-            list_players(player_id, writer, state);
-            Ok(())
+    };
+    let command_head = command_head.as_str();
+
+    if let Some(command) = game_command::find(command_head, player.role) {
+        return command.run(player_id, words, writer, state);
+    }
+    if let Some(skill) = game_skill::find_learned(command_head, player, state) {
+        return game_skill::use_skill(player_id, skill.clone(), words, writer, state);
+    }
+    let room_specific_command =
+        resolve_room_specific_command(command_head, words, player.room_id, state)?;
+    match room_specific_command {
+        Some(RoomSpecificCommand::Exit { to_room_id }) => {
+            let in_combat = game_combat::is_in_combat(player, state);
+            game_travel::cancel_travel(player_id, "You stop travelling.", writer, state);
+            if in_combat {
+                game_combat::flee_through(player_id, command_head, to_room_id, writer, state)
+            } else {
+                move_self(player_id, to_room_id, command_head, writer, state)
+            }
         }
-        "help" if words.is_empty() => {
-            game_help::help(player_id, writer);
+        Some(RoomSpecificCommand::RoomCommand { room_command }) => {
+            run_room_command(
+                &room_command.clone(),
+                player_id,
+                player.room_id,
+                writer,
+                state,
+            );
             Ok(())
         }
-        "alias" if words.is_empty() => {
-            game_alias::alias(player_id, writer);
+        None => {
+            unknown_command(player_id, &[], writer);
             Ok(())
         }
-        "roll" if words.is_empty() => roll_die(player, writer, state),
-
-        other_command => {
-            let room_specific_command =
-                resolve_room_specific_command(other_command, words, player.room_id, state)?;
-            match room_specific_command {
-                Some(RoomSpecificCommand::Exit { to_room_id }) => {
-                    move_self(player_id, to_room_id, other_command, writer, state)
-                }
-                Some(RoomSpecificCommand::RoomCommand { room_command }) => {
-                    run_room_command(
-                        &room_command.clone(),
-                        player_id,
-                        player.room_id,
-                        writer,
-                        state,
-                    );
-                    Ok(())
-                }
-                None => {
-                    writer.tell(player_id, Line::str("Unknown command."));
-                    Ok(())
-                }
-            }
-        }
     }
 }
 
-fn look(
+pub fn complete_command_head(
+    word: &str,
     player: &Player,
+    state: &GameState,
+) -> Result<Completion, String> {
+    let room = state.rooms.get(&player.room_id).ok_or("complete: Room not found")?;
+    let mut candidates = game_command::available(player.role)
+        .map(|command| command.name)
+        .collect::<Vec<_>>();
+    candidates.extend(room_command_names(room, state));
+    candidates.extend(player.skills.iter().map(String::as_str));
+    Ok(game_command::complete(word, candidates))
+}
+
+fn unknown_command(player_id: Id<Player>, suggestions: &[String], writer: &mut EventWriter) {
+    if suggestions.is_empty() {
+        writer.tell(player_id, Line::str("Unknown command."));
+    } else {
+        let suggestions = suggestions.iter().map(|s| span(s).color(Color::White)).collect();
+        writer.tell(
+            player_id,
+            Line::str("Unknown command. Did you mean ")
+                .extend(or_spans(suggestions))
+                .push(span("?")),
+        );
+    }
+}
+
+pub fn look(
+    player_id: Id<Player>,
     mut words: Vec<&str>,
     writer: &mut EventWriter,
-    state: &GameState,
+    state: &mut GameState,
 ) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("look: Self not found")?;
     let room = state.rooms.get(&player.room_id).ok_or("look: Room not found")?;
 
     if words.is_empty() {
@@ -208,7 +235,11 @@ fn look(
         let words = words;
 
         let target_str = words.join(" ");
-        if let Some(target) = resolve_target_in_room(&target_str, room, state.mobs.by_id()) {
+        let targets = resolve_targets_in_room(&target_str, room, &state.mobs);
+        if targets.is_empty() {
+            writer.tell(player.id, Line::str("You do not see that here."));
+        }
+        for target in targets {
             match target {
                 RoomTarget::RoomObject { room_object: obj } => {
                     if let Some(desc) = eval_room_description(&obj.description, room.id, state) {
@@ -220,22 +251,29 @@ fn look(
                     );
                 }
                 RoomTarget::Mob { mob } => {
+                    let effects = game_status::describe_effects(
+                        "It is",
+                        &mob.status_effects,
+                        state.ticks,
+                        false,
+                    );
                     let mob = &mob.template;
                     writer.tell(player.id, Line::str(&mob.description));
+                    if let Some(effects) = effects {
+                        writer.tell(player.id, effects);
+                    }
                     writer.tell_many(
                         state.players.ids_in_room_except(room.id, player.id),
                         Line::str(&format!("{} looks at the {}.", &player.name, &mob.name)),
                     );
                 }
             }
-        } else {
-            writer.tell(player.id, Line::str("You do not see that here."));
         }
     }
     Ok(())
 }
 
-fn move_self(
+pub fn move_self(
     player_id: Id<Player>,
     to_room_id: Id<Room>,
     exit: &str,
@@ -247,11 +285,7 @@ fn move_self(
         let from_room_id = player.room_id;
         let player_name = player.name.clone();
         player.room_id = to_room_id;
-
-        if player.attack_target.is_some() {
-            player.attack_target = None;
-            writer.tell(player_id, Line::str("You flee."));
-        }
+        player.attack_target = None;
         (from_room_id, player_name)
     });
 
@@ -269,7 +303,7 @@ fn move_self(
             .line(),
     );
 
-    describe_room(player_id, to_room, writer, state);
+    arrive_in_room(player_id, to_room_id, writer, state);
     Ok(())
 }
 
@@ -282,15 +316,106 @@ fn format_player_count(count: usize) -> String {
     )
 }
 
-fn list_players(player_id: Id<Player>, writer: &mut EventWriter, state: &GameState) {
+pub fn list_players(player_id: Id<Player>, writer: &mut EventWriter, state: &GameState) {
     let mut lines = vec![Line::str(&format_player_count(state.players.by_id().len()))];
-    lines.extend(state.players.by_id().values().map(|player| Line::str(&player.name)));
+    lines.extend(state.players.by_id().values().map(|player| {
+        match game_zone::whereabouts(player, state) {
+            Some(zone_name) => Line::str(&format!("{} ({})", player.name, zone_name)),
+            None => Line::str(&player.name),
+        }
+    }));
     writer.tell_lines(player_id, &lines)
 }
 
-fn roll_die(player: &Player, writer: &mut EventWriter, state: &GameState) -> Result<(), String> {
-    let mut rng = thread_rng();
-    let roll: u32 = rng.gen_range(1..=6);
+pub fn score(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("score: Self not found")?;
+    let mut lines = vec![
+        span(&player.name).bold().line(),
+        Line::str(&format!("Health: {}/{}", player.hp, player.max_hp)),
+        Line::str(&format!("Mana: {}/{}", player.mana, player.max_mana)),
+    ];
+    if let Some(wimpy) = player.wimpy {
+        lines.push(Line::str(&format!("Wimpy: {}", wimpy)));
+    }
+    if player.pvp {
+        lines.push(Line::str("You are open to fights with other players."));
+    }
+    if player.player_kills > 0 {
+        lines.push(Line::str(&format!(
+            "Players killed: {}",
+            player.player_kills
+        )));
+    }
+    lines.extend(game_status::describe_effects(
+        "You are",
+        &player.status_effects,
+        state.ticks,
+        true,
+    ));
+    lines.push(Line::str(&format!(
+        "Explored: {}",
+        format_exploration(player, state.rooms.values())
+    )));
+
+    let mut zones = state.zones.values().collect::<Vec<_>>();
+    zones.sort_by_key(|zone| zone.id.value);
+    lines.extend(zones.into_iter().map(|zone| {
+        let rooms = state.rooms.values().filter(|room| room.zone_id == zone.id);
+        Line::str(&format!(
+            "  {}: {}",
+            zone.name,
+            format_exploration(player, rooms)
+        ))
+    }));
+    writer.tell_lines(player_id, &lines);
+    Ok(())
+}
+
+fn format_exploration<'a>(player: &Player, rooms: impl Iterator<Item = &'a Room>) -> String {
+    let (explored_count, room_count) = rooms.fold((0, 0), |(explored, total), room| {
+        (
+            explored + usize::from(player.has_visited(room.id)),
+            total + 1,
+        )
+    });
+    format!(
+        "{} of {} {} ({}%)",
+        explored_count,
+        room_count,
+        plural(room_count, "room"),
+        percentage(explored_count, room_count)
+    )
+}
+
+fn percentage(part: usize, total: usize) -> usize {
+    (part * 100).checked_div(total).unwrap_or(0)
+}
+
+pub fn time(player_id: Id<Player>, writer: &mut EventWriter, state: &GameState) {
+    let hour = state.game_hour();
+    let part_of_day = match hour {
+        0..=5 => "night",
+        6..=11 => "morning",
+        12..=17 => "afternoon",
+        _ => "evening",
+    };
+    writer.tell(
+        player_id,
+        Line::str(&format!("It is {}:00, {}.", hour, part_of_day)),
+    );
+}
+
+pub fn roll_die(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let roll: u32 = state.rng.gen_range(1..=6);
+    let player = state.players.by_id().get(&player_id).ok_or("roll: Self not found")?;
     writer.tell(player.id, Line::str(&format!("You rolled a {}.", roll)));
     writer.tell_many(
         state.players.ids_in_room_except(player.room_id, player.id),
@@ -298,24 +423,3 @@ fn roll_die(player: &Player, writer: &mut EventWriter, state: &GameState) -> Res
     );
     Ok(())
 }
-
-fn spawn_mobs(room_ids_templates: Vec<(Id<Room>, MobTemplate)>, state: &mut GameState) {
-    let GameState { mobs, mob_id_source, .. } = state;
-    room_ids_templates
-        .into_iter()
-        .map(|(room_id, template)| {
-            let id = mob_id_source.next();
-            let hp = template.max_hp;
-            let attack_offset = template.attack_period.random_offset(&mut thread_rng());
-            Mob {
-                id,
-                room_id,
-                template,
-                hp,
-                attack_offset,
-                hostile_to: HashSet::new(),
-                attack_target: None,
-            }
-        })
-        .for_each(|mob| mobs.insert(mob));
-}
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    event_writer::EventWriter,
+    game_room::exit_target,
+    game_state::{GameState, Room},
+    id::Id,
+    line::{span, Color, Line, LineSpan},
+    player::Player,
+};
+
+static DEFAULT_RADIUS: i32 = 3;
+static MAX_RADIUS: i32 = 6;
+
+static CELL_WIDTH: i32 = 4;
+static CELL_HEIGHT: i32 = 2;
+
+type Pos = (i32, i32);
+
+pub fn map(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("map: Self not found")?;
+    let radius = match words.first().map(|word| word.parse::<i32>()) {
+        None => DEFAULT_RADIUS,
+        Some(Ok(radius)) if (1..=MAX_RADIUS).contains(&radius) => radius,
+        Some(_) => {
+            let line = format!("The map radius must be between 1 and {}.", MAX_RADIUS);
+            writer.tell(player_id, Line::str(&line));
+            return Ok(());
+        }
+    };
+
+    // Only rooms the player has been to, so the map gives away no unexplored or secret areas.
+    let positions = layout(player.room_id, radius, state)
+        .into_iter()
+        .filter(|(room_id, _)| player.has_visited(*room_id))
+        .collect::<HashMap<_, _>>();
+    let mut canvas = Canvas::new(radius);
+    for (room_id, pos) in &positions {
+        let Some(room) = state.rooms.get(room_id) else {
+            continue;
+        };
+        canvas.draw_room(*pos, room_marker(room, player, state));
+        for exit in room.exits.values() {
+            let (to, is_open) = exit_target(exit, room.id, state);
+            if let Some(to_pos) = positions.get(&to) {
+                canvas.draw_exit(*pos, *to_pos, is_open);
+            }
+        }
+    }
+
+    let mut lines = canvas.into_lines();
+    lines.push(Line::spans(vec![
+        span("@").color(Color::White).bold(),
+        span(" you  "),
+        span("P").color(Color::Blue),
+        span(" players  "),
+        span("M").color(Color::Orange),
+        span(" mobs  "),
+        span("^v").color(Color::Cyan),
+        span(" up/down  "),
+        span("#").color(Color::DarkGrey),
+        span(" closed"),
+    ]));
+    writer.tell_lines(player_id, &lines);
+    Ok(())
+}
+
+/// Places the rooms around the center on a grid. Rooms with coordinates keep their relative
+/// positions, others are placed by the compass direction of the exit leading to them. A room
+/// that would land on an occupied cell or outside the radius is left out.
+pub fn layout(center: Id<Room>, radius: i32, state: &GameState) -> HashMap<Id<Room>, Pos> {
+    let mut positions = HashMap::from([(center, (0, 0))]);
+    let mut occupied = HashMap::from([((0, 0), center)]);
+    let mut queue = VecDeque::from([center]);
+
+    while let Some(room_id) = queue.pop_front() {
+        let Some(room) = state.rooms.get(&room_id) else {
+            continue;
+        };
+        let (x, y) = positions[&room_id];
+        for (direction, exit) in &room.exits {
+            let (to, _) = exit_target(exit, room.id, state);
+            let Some(to_room) = state.rooms.get(&to) else {
+                continue;
+            };
+            let offset = match (room.coords, to_room.coords) {
+                (Some((fx, fy)), Some((tx, ty))) => Some((tx - fx, ty - fy)),
+                _ => compass_offset(direction),
+            };
+            let Some((dx, dy)) = offset.filter(|offset| *offset != (0, 0)) else {
+                continue;
+            };
+            let pos = (x + dx, y + dy);
+            if positions.contains_key(&to)
+                || occupied.contains_key(&pos)
+                || pos.0.abs() > radius
+                || pos.1.abs() > radius
+            {
+                continue;
+            }
+            positions.insert(to, pos);
+            occupied.insert(pos, to);
+            queue.push_back(to);
+        }
+    }
+    positions
+}
+
+fn compass_offset(direction: &str) -> Option<Pos> {
+    match direction {
+        "north" => Some((0, -1)),
+        "northeast" => Some((1, -1)),
+        "east" => Some((1, 0)),
+        "southeast" => Some((1, 1)),
+        "south" => Some((0, 1)),
+        "southwest" => Some((-1, 1)),
+        "west" => Some((-1, 0)),
+        "northwest" => Some((-1, -1)),
+        _ => None,
+    }
+}
+
+fn room_marker(room: &Room, player: &Player, state: &GameState) -> LineSpan {
+    let has_mobs = state.mobs.by_room_id().get(&room.id).is_some_and(|mobs| !mobs.is_empty());
+    let has_players = state.players.ids_in_room_except(room.id, player.id).next().is_some();
+
+    if room.id == player.room_id {
+        span("@").color(Color::White).bold()
+    } else if has_mobs {
+        span("M").color(Color::Orange)
+    } else if has_players {
+        span("P").color(Color::Blue)
+    } else if room.exits.contains_key("up") {
+        span("^").color(Color::Cyan)
+    } else if room.exits.contains_key("down") {
+        span("v").color(Color::Cyan)
+    } else {
+        span(" ")
+    }
+}
+
+/// A grid of single-character spans. Each room is drawn as a three character wide cell with
+/// exits drawn in the gaps between neighbouring cells.
+struct Canvas {
+    radius: i32,
+    cells: Vec<Vec<Option<LineSpan>>>,
+}
+
+impl Canvas {
+    fn new(radius: i32) -> Canvas {
+        let size = 2 * radius + 1;
+        let width = (size * CELL_WIDTH - 1) as usize;
+        let height = (size * CELL_HEIGHT - 1) as usize;
+        Canvas { radius, cells: vec![vec![None; width]; height] }
+    }
+
+    /// The canvas column and row of the center of a room's cell.
+    fn center((x, y): Pos, radius: i32) -> Pos {
+        ((x + radius) * CELL_WIDTH + 1, (y + radius) * CELL_HEIGHT)
+    }
+
+    fn set(&mut self, (col, row): Pos, span: LineSpan) {
+        if let Some(cell) = self.cells.get_mut(row as usize).and_then(|r| r.get_mut(col as usize)) {
+            *cell = Some(span);
+        }
+    }
+
+    fn is_set(&self, (col, row): Pos) -> bool {
+        self.cells
+            .get(row as usize)
+            .and_then(|r| r.get(col as usize))
+            .is_some_and(|cell| cell.is_some())
+    }
+
+    fn draw_room(&mut self, pos: Pos, marker: LineSpan) {
+        let (col, row) = Canvas::center(pos, self.radius);
+        self.set((col - 1, row), span("["));
+        self.set((col, row), marker);
+        self.set((col + 1, row), span("]"));
+    }
+
+    /// Draws the way between two neighbouring rooms. Open ways take precedence, as a closed
+    /// exit in one direction may still be open from the other side.
+    fn draw_exit(&mut self, from: Pos, to: Pos, is_open: bool) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        if dx.abs() > 1 || dy.abs() > 1 {
+            return;
+        }
+        let (from_col, from_row) = Canvas::center(from, self.radius);
+        let (to_col, to_row) = Canvas::center(to, self.radius);
+        let midpoint = ((from_col + to_col) / 2, (from_row + to_row) / 2);
+
+        if !is_open {
+            if !self.is_set(midpoint) {
+                self.set(midpoint, span("#").color(Color::DarkGrey));
+            }
+            return;
+        }
+        let connector = match (dx, dy) {
+            (_, 0) => "-",
+            (0, _) => "|",
+            (1, 1) | (-1, -1) => "\\",
+            _ => "/",
+        };
+        self.set(midpoint, span(connector));
+    }
+
+    /// Renders the canvas cropped to the drawn area, merging neighbouring characters of the
+    /// same style into one span.
+    fn into_lines(self) -> Vec<Line> {
+        let drawn = |row: &Vec<Option<LineSpan>>| row.iter().any(Option::is_some);
+        let first_row = self.cells.iter().position(drawn).unwrap_or(0);
+        let last_row = self.cells.iter().rposition(drawn).unwrap_or(0);
+        let first_col = self
+            .cells
+            .iter()
+            .filter_map(|row| row.iter().position(Option::is_some))
+            .min()
+            .unwrap_or(0);
+
+        self.cells
+            .into_iter()
+            .skip(first_row)
+            .take(last_row + 1 - first_row)
+            .map(|row| {
+                let last_col = row.iter().rposition(Option::is_some).map_or(0, |col| col + 1);
+                let mut spans: Vec<LineSpan> = Vec::new();
+                for cell in row.into_iter().take(last_col).skip(first_col) {
+                    let cell = cell.unwrap_or_else(|| span(" "));
+                    match spans.last_mut() {
+                        Some(last) if last.color == cell.color && last.bold == cell.bold => {
+                            last.text.push_str(&cell.text)
+                        }
+                        _ => spans.push(cell),
+                    }
+                }
+                Line::spans(spans)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,216 @@
+use log::warn;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    event_writer::EventWriter, game_alias, game_command::Completion, game_logic,
+    game_state::GameState, game_travel, id::Id, line::Line, player::Player, text_util::plural,
+};
+
+static SPEEDWALK_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:\d*[neswud])*\d[neswud](?:\d*[neswud])*$").unwrap());
+static SPEEDWALK_STEP_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d*)([neswud])").unwrap());
+
+static COMMAND_SEPARATOR: char = ';';
+static MAX_SPEEDWALK_REPEAT: usize = 20;
+
+/// Splits an input line into commands, expands aliases and speedwalks, and queues the
+/// results. Queued commands run as soon as the player's per-tick budget allows.
+pub fn on_input(
+    player_id: Id<Player>,
+    input: &str,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let max_queued = state.config.max_queued_commands;
+    let mut overflow = false;
+
+    let player = state.players.by_id().get(&player_id).ok_or("input: Self not found")?;
+    for segment in split_input(input, is_alias_definition(input, player, state)) {
+        if segment.eq_ignore_ascii_case("stop") {
+            stop(player_id, writer, state);
+            continue;
+        }
+
+        let player = state.players.by_id().get(&player_id).ok_or("input: Self not found")?;
+        let Some(commands) = game_alias::expand_user_aliases(segment, &player.aliases) else {
+            writer.tell(player_id, Line::str("That alias expands too much."));
+            continue;
+        };
+        let commands = commands
+            .iter()
+            .flat_map(|command| expand_speedwalk(command, player, state))
+            .collect::<Vec<_>>();
+
+        state.players.modify(&player_id, |player| {
+            for command in commands {
+                if player.command_queue.len() < max_queued {
+                    player.command_queue.push_back(command);
+                } else {
+                    overflow = true;
+                }
+            }
+        });
+    }
+
+    if overflow {
+        writer.tell(
+            player_id,
+            Line::str("Too many commands queued, the rest were dropped."),
+        );
+    }
+    run_queued_commands(player_id, writer, state);
+    Ok(())
+}
+
+/// Splits an input line into commands. Alias definitions are kept whole, since their `;`
+/// separate the commands the alias runs rather than commands to run now.
+fn split_input(input: &str, is_alias_definition: bool) -> Vec<&str> {
+    if is_alias_definition {
+        vec![input.trim()]
+    } else {
+        input
+            .split(COMMAND_SEPARATOR)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
+
+fn is_alias_definition(input: &str, player: &Player, state: &GameState) -> bool {
+    matches!(
+        complete_head(input, player, state),
+        Some(Completion::Unique(command)) if command == "alias"
+    )
+}
+
+/// Completes the first word of the command the way `game_logic::on_command` would.
+fn complete_head(command: &str, player: &Player, state: &GameState) -> Option<Completion> {
+    let head = command.split_whitespace().next()?.to_ascii_lowercase();
+    let words = game_alias::resolve_aliases(vec![&head], player);
+    game_logic::complete_command_head(words[0], player, state).ok()
+}
+
+pub fn tick_command_queues(writer: &mut EventWriter, state: &mut GameState) {
+    let commands_per_tick = state.config.commands_per_tick;
+    let player_ids = state.players.by_id().keys().copied().collect::<Vec<_>>();
+    for player_id in player_ids {
+        state.players.modify(&player_id, |player| {
+            player.command_budget = commands_per_tick
+        });
+        run_queued_commands(player_id, writer, state);
+    }
+}
+
+fn run_queued_commands(player_id: Id<Player>, writer: &mut EventWriter, state: &mut GameState) {
+    loop {
+        let command = match state.players.by_id().get(&player_id) {
+            Some(player) if player.command_budget > 0 && !player.is_stunned(state.ticks) => {
+                state.players.modify(&player_id, |player| {
+                    let command = player.command_queue.pop_front();
+                    if command.is_some() {
+                        player.command_budget -= 1;
+                    }
+                    command
+                })
+            }
+            _ => None,
+        };
+        match command {
+            Some(command) => {
+                if let Err(err) = game_logic::on_command(player_id, &command, writer, state) {
+                    warn!("Player command: {}", err);
+                }
+            }
+            None => break,
+        }
+    }
+}
+
+/// Turns `4n2e` into `north` four times and `east` twice. Anything else, including words that
+/// look like speedwalks but name a command, exit or skill, is left as is.
+fn expand_speedwalk(command: &str, player: &Player, state: &GameState) -> Vec<String> {
+    if !SPEEDWALK_REGEX.is_match(command)
+        || matches!(
+            complete_head(command, player, state),
+            Some(Completion::Unique(_) | Completion::Ambiguous(_))
+        )
+    {
+        return vec![command.to_string()];
+    }
+    SPEEDWALK_STEP_REGEX
+        .captures_iter(command)
+        .flat_map(|captures| {
+            let repeat = captures[1].parse::<usize>().unwrap_or(1).min(MAX_SPEEDWALK_REPEAT);
+            let direction = game_alias::resolve_direction(&captures[2]).to_string();
+            std::iter::repeat_n(direction, repeat)
+        })
+        .collect()
+}
+
+pub fn stop(player_id: Id<Player>, writer: &mut EventWriter, state: &mut GameState) {
+    let cleared = state.players.modify(&player_id, |player| {
+        let cleared = player.command_queue.len();
+        player.command_queue.clear();
+        cleared
+    });
+    let was_travelling = state
+        .players
+        .by_id()
+        .get(&player_id)
+        .is_some_and(|player| !player.travel_path.is_empty());
+    game_travel::cancel_travel(player_id, "You stop travelling.", writer, state);
+
+    if cleared > 0 {
+        let line = format!(
+            "You drop {} queued {}.",
+            cleared,
+            plural(cleared, "command")
+        );
+        writer.tell(player_id, Line::str(&line));
+    } else if !was_travelling {
+        writer.tell(player_id, Line::str("You have nothing to stop."));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{id::Id, test_util};
+
+    #[test]
+    fn alias_definitions_keep_their_separators() {
+        let input = "alias k kill $1; say Die, $1!";
+        assert_eq!(split_input(input, true), vec![input]);
+        assert_eq!(
+            split_input(input, false),
+            vec!["alias k kill $1", "say Die, $1!"]
+        );
+    }
+
+    #[test]
+    fn speedwalks_do_not_shadow_commands() {
+        let rooms = "
+- id: 0
+  name: Library
+  zoneId: 0
+  description: Shelves of books.
+  exits: { north: 0 }
+  objects:
+  - name: book
+    description: A dusty book.
+    commands:
+    - command: 2nd
+      statements: []
+";
+        let mut state = test_util::game_state(rooms, "[]");
+        let player_id = test_util::add_player("Ann", Id::new(0), &mut state);
+        let player = &state.players.by_id()[&player_id];
+
+        assert_eq!(expand_speedwalk("2nd", player, &state), vec!["2nd"]);
+        assert_eq!(
+            expand_speedwalk("2n1e", player, &state),
+            vec!["north", "north", "east"]
+        );
+    }
+}
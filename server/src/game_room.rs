@@ -1,11 +1,16 @@
+use std::collections::{HashMap, VecDeque};
+
 use crate::{
     event_writer::EventWriter,
+    game_consider::Danger,
+    game_skill,
     game_state::{
         Condition, GameState, Room, RoomCommand, RoomDescription, RoomExit, RoomObject, Statement,
     },
-    id::{Id, IdMap},
+    id::Id,
     line::{span, Color, Line},
     mob::Mob,
+    mob_coll::MobColl,
     named::Named,
     player::Player,
     text_util::and_span_vecs,
@@ -17,25 +22,98 @@ pub enum RoomTarget<'a, 'b> {
     Mob { mob: &'b Mob },
 }
 
-pub fn resolve_target_in_room<'a, 'b>(
+impl Named for RoomTarget<'_, '_> {
+    fn get_name(&self) -> &String {
+        match self {
+            RoomTarget::RoomObject { room_object } => room_object.get_name(),
+            RoomTarget::Mob { mob } => mob.template.get_name(),
+        }
+    }
+
+    fn get_aliases(&self) -> &[String] {
+        match self {
+            RoomTarget::RoomObject { room_object } => room_object.get_aliases(),
+            RoomTarget::Mob { mob } => mob.template.get_aliases(),
+        }
+    }
+}
+
+enum TargetSelector {
+    Nth(usize),
+    All,
+}
+
+static ORDINALS: &[&str] = &[
+    "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth",
+];
+
+/// Splits `2.skeleton`, `second skeleton` or `all.skeleton` into a selector and a name.
+fn parse_target(target: &str) -> (TargetSelector, &str) {
+    if let Some((prefix, name)) = target.split_once('.') {
+        if prefix.eq_ignore_ascii_case("all") {
+            return (TargetSelector::All, name);
+        } else if let Ok(n) = prefix.parse::<usize>() {
+            return (TargetSelector::Nth(n.max(1)), name);
+        }
+    }
+    if let Some((first_word, name)) = target.split_once(' ') {
+        if first_word.eq_ignore_ascii_case("all") {
+            return (TargetSelector::All, name);
+        } else if let Some(i) = ORDINALS.iter().position(|o| o.eq_ignore_ascii_case(first_word)) {
+            return (TargetSelector::Nth(i + 1), name);
+        }
+    }
+    (TargetSelector::Nth(1), target)
+}
+
+/// The mobs in the room, in the order they spawned.
+pub fn mobs_in_room(room_id: Id<Room>, mobs: &MobColl) -> Vec<&Mob> {
+    let mut mobs_in_room = mobs
+        .by_room_id()
+        .get(&room_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|mob_id| mobs.by_id().get(mob_id))
+        .collect::<Vec<_>>();
+    mobs_in_room.sort_by_key(|mob| mob.id.value);
+    mobs_in_room
+}
+
+/// Resolves the mobs and objects in the room that the target refers to. Mobs come first in
+/// the order they spawned, then objects in the order they are listed for the room. Exact
+/// name matches take precedence over abbreviations.
+pub fn resolve_targets_in_room<'a, 'b>(
     target: &str,
     room: &'a Room,
-    mobs: &'b IdMap<Mob>,
-) -> Option<RoomTarget<'a, 'b>> {
+    mobs: &'b MobColl,
+) -> Vec<RoomTarget<'a, 'b>> {
     use RoomTarget::*;
 
-    let mobs = mobs
-        .values()
-        .filter(|mob| mob.room_id == room.id && mob.template.matches(target))
-        .map(|mob| Mob { mob });
+    let (selector, name) = parse_target(target.trim());
 
-    let room_objects = room
-        .objects
-        .iter()
-        .filter(|room_object| room_object.matches(target))
-        .map(|room_object| RoomObject { room_object });
+    let candidates = mobs_in_room(room.id, mobs)
+        .into_iter()
+        .map(|mob| Mob { mob })
+        .chain(room.objects.iter().map(|room_object| RoomObject { room_object }))
+        .collect::<Vec<_>>();
 
-    mobs.chain(room_objects).next()
+    let has_exact_match = candidates.iter().any(|candidate| candidate.matches(name));
+    let mut matching = candidates
+        .into_iter()
+        .filter(|candidate| {
+            if has_exact_match {
+                candidate.matches(name)
+            } else {
+                candidate.matches_partial(name)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match selector {
+        TargetSelector::All => matching,
+        TargetSelector::Nth(n) if n <= matching.len() => vec![matching.swap_remove(n - 1)],
+        TargetSelector::Nth(_) => vec![],
+    }
 }
 
 pub enum RoomSpecificCommand<'a> {
@@ -63,10 +141,12 @@ pub fn resolve_room_specific_command<'a>(
         }
     }) {
         Ok(Some(RoomSpecificCommand::Exit { to_room_id: *to_room_id }))
-    } else if let Some(room_command) = room
-        .objects
-        .iter()
-        .filter(|obj| obj.matches(&args_joined))
+    } else if let Some(room_command) = resolve_targets_in_room(&args_joined, room, &state.mobs)
+        .into_iter()
+        .filter_map(|target| match target {
+            RoomTarget::RoomObject { room_object } => Some(room_object),
+            RoomTarget::Mob { .. } => None,
+        })
         .flat_map(|obj| obj.commands.iter())
         .find(|room_command| {
             if room_command.command != command {
@@ -84,6 +164,92 @@ pub fn resolve_room_specific_command<'a>(
     }
 }
 
+pub fn open_exits<'a>(
+    room: &'a Room,
+    state: &'a GameState,
+) -> impl Iterator<Item = (&'a str, Id<Room>)> + 'a {
+    room.exits.iter().filter_map(move |(direction, exit)| {
+        let (to, is_open) = exit_target(exit, room.id, state);
+        Some((direction.as_str(), to)).filter(|_| is_open)
+    })
+}
+
+/// The room an exit leads to, and whether it is currently open.
+pub fn exit_target(exit: &RoomExit, room_id: Id<Room>, state: &GameState) -> (Id<Room>, bool) {
+    match exit {
+        RoomExit::Static(to) => (*to, true),
+        RoomExit::Conditional { condition, to } => {
+            (*to, eval_room_condition(condition, room_id, state))
+        }
+    }
+}
+
+/// Finds the shortest way through open exits from one room to the nearest room accepted by
+/// `is_goal`, only passing rooms accepted by `can_pass`. Returns the rooms along the way,
+/// excluding the starting room.
+pub fn find_path(
+    from: Id<Room>,
+    state: &GameState,
+    can_pass: impl Fn(Id<Room>) -> bool,
+    is_goal: impl Fn(&Room) -> bool,
+) -> Option<Vec<Id<Room>>> {
+    let mut previous: HashMap<Id<Room>, Id<Room>> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(room_id) = queue.pop_front() {
+        let Some(room) = state.rooms.get(&room_id) else {
+            continue;
+        };
+        if room_id != from && is_goal(room) {
+            let mut path = vec![room_id];
+            while let Some(prev) = previous.get(path.last()?).filter(|prev| **prev != from) {
+                path.push(*prev);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (_, to) in open_exits(room, state) {
+            if to != from && !previous.contains_key(&to) && can_pass(to) {
+                previous.insert(to, room_id);
+                queue.push_back(to);
+            }
+        }
+    }
+    None
+}
+
+/// Words that are commands only in the given room: open exits and object commands.
+pub fn room_command_names<'a>(room: &'a Room, state: &GameState) -> Vec<&'a str> {
+    let exits = room.exits.iter().filter_map(|(direction, exit)| match exit {
+        RoomExit::Static(_) => Some(direction.as_str()),
+        RoomExit::Conditional { condition, .. } => {
+            Some(direction.as_str()).filter(|_| eval_room_condition(condition, room.id, state))
+        }
+    });
+    let object_commands = room
+        .objects
+        .iter()
+        .flat_map(|obj| obj.commands.iter().map(|room_command| room_command.command.as_str()));
+    exits.chain(object_commands).collect()
+}
+
+pub fn reverse_direction(direction: &str) -> Option<&'static str> {
+    match direction {
+        "north" => Some("south"),
+        "northeast" => Some("southwest"),
+        "east" => Some("west"),
+        "southeast" => Some("northwest"),
+        "south" => Some("north"),
+        "southwest" => Some("northeast"),
+        "west" => Some("east"),
+        "northwest" => Some("southeast"),
+        "up" => Some("down"),
+        "down" => Some("up"),
+        "in" => Some("out"),
+        "out" => Some("in"),
+        _ => None,
+    }
+}
+
 pub fn eval_room_condition(condition: &Condition, room_id: Id<Room>, state: &GameState) -> bool {
     match condition {
         Condition::Equals(var, value) => state.get_room_var(room_id, var.to_string()) == *value,
@@ -105,7 +271,7 @@ pub fn eval_room_description(
                     if branch
                         .condition
                         .as_ref()
-                        .map_or(true, |cond| eval_room_condition(cond, room_id, state))
+                        .is_none_or(|cond| eval_room_condition(cond, room_id, state))
                     {
                         Some(branch.fragment.as_str())
                     } else {
@@ -147,6 +313,15 @@ pub fn run_room_command(
             Statement::TellRoom(line) => {
                 writer.tell_many(state.players.ids_in_room(room_id), Line::str(line));
             }
+            Statement::ApplyStatus(application) => {
+                let now = state.ticks;
+                state.players.modify(&self_id, |player| {
+                    player.status_effects.apply(application, now)
+                });
+            }
+            Statement::LearnSkill(skill_name) => {
+                game_skill::learn_skill(self_id, skill_name, writer, state);
+            }
             Statement::ResetRoomVarAfterSecs(var, secs, message) => {
                 state.scheduled_room_var_resets.insert(
                     state.ticks + TickDuration::from_secs(*secs),
@@ -157,30 +332,51 @@ pub fn run_room_command(
     }
 }
 
+/// Describes the room the player has just entered and records the visit. Rooms are
+/// highlighted in the description until the player has been there once.
+pub fn arrive_in_room(
+    player_id: Id<Player>,
+    room_id: Id<Room>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) {
+    if let Some(room) = state.rooms.get(&room_id) {
+        describe_room(player_id, room, writer, state);
+    }
+    state.players.modify(&player_id, |player| player.visited_rooms.insert(room_id));
+}
+
 pub fn describe_room(
     self_id: Id<Player>,
     room: &Room,
     writer: &mut EventWriter,
     state: &GameState,
 ) {
+    let is_first_visit = state
+        .players
+        .by_id()
+        .get(&self_id)
+        .is_some_and(|player| !player.has_visited(room.id));
     let mut lines = Vec::new();
-    lines.push(span(&room.name).bold().line());
+    lines.push(if is_first_visit {
+        span(&room.name).bold().color(Color::Yellow).line().push(span(" (new)"))
+    } else {
+        span(&room.name).bold().line()
+    });
     if let Some(line) = eval_room_description(&room.description, room.id, state) {
         lines.push(Line::str(&line));
     }
-    {
+    if let Some(self_player) = state.players.by_id().get(&self_id) {
         let players = state
             .players
             .by_id()
             .values()
             .filter(|player| player.id != self_id && player.room_id == room.id)
             .map(|player| vec![span(&player.name).color(Color::Blue)]);
-        let mobs = state
-            .mobs
-            .by_id()
-            .values()
-            .filter(|mob| mob.room_id == room.id)
-            .map(|mob| vec![span("a "), span(&mob.template.name).color(Color::Orange)]);
+        let mobs = mobs_in_room(room.id, &state.mobs).into_iter().map(|mob| {
+            let danger = Danger::of(mob, self_player);
+            vec![span("a "), span(&mob.template.name).color(danger.color())]
+        });
         let all = players.chain(mobs).collect::<Vec<_>>();
         if !all.is_empty() {
             let line = Line::str("You see ").extend(and_span_vecs(all)).push(span(" here."));
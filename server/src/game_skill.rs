@@ -0,0 +1,319 @@
+use log::warn;
+use once_cell::sync::Lazy;
+
+use crate::{
+    event_writer::EventWriter,
+    game_combat,
+    game_room::{resolve_targets_in_room, RoomTarget},
+    game_state::GameState,
+    game_zone,
+    id::Id,
+    line::{span, Color, Line},
+    player::{AttackTarget, Player},
+    skill::{Skill, SkillTarget},
+    tick::TickDuration,
+};
+
+pub static MANA_REGEN_FREQ: Lazy<TickDuration> = Lazy::new(|| TickDuration::from_secs(2.0));
+
+pub fn skills(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("skills: Self not found")?;
+    let mut lines = vec![Line::str(&format!(
+        "Mana: {}/{}",
+        player.mana, player.max_mana
+    ))];
+    if player.skills.is_empty() {
+        lines.push(Line::str("You have not learned any skills."));
+    }
+    for skill in player.skills.iter().filter_map(|name| state.skills.get(name)) {
+        let status = if is_ready(player, skill, state) {
+            span("ready").color(Color::LightGreen)
+        } else {
+            span("cooling down").color(Color::DarkGrey)
+        };
+        lines.push(
+            span(&skill.name)
+                .bold()
+                .line()
+                .push(span(&format!(
+                    " - {} ({} mana, {}s cooldown) ",
+                    skill.description,
+                    skill.mana_cost,
+                    skill.cooldown.as_secs()
+                )))
+                .push(status),
+        );
+    }
+    writer.tell_lines(player_id, &lines);
+    Ok(())
+}
+
+/// The learned skill a command word refers to, if any.
+pub fn find_learned<'a>(word: &str, player: &Player, state: &'a GameState) -> Option<&'a Skill> {
+    state.skills.get(word).filter(|skill| player.skills.contains(&skill.name))
+}
+
+pub fn use_skill(
+    player_id: Id<Player>,
+    skill: Skill,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("use_skill: Self not found")?;
+    if !is_ready(player, &skill, state) {
+        let msg = format!("You cannot use {} again yet.", skill.name);
+        writer.tell(player_id, Line::str(&msg));
+        return Ok(());
+    }
+    if player.mana < skill.mana_cost {
+        writer.tell(player_id, Line::str("You do not have enough mana."));
+        return Ok(());
+    }
+    match skill.target {
+        SkillTarget::Enemy => use_on_enemy(player_id, &skill, words, writer, state),
+        SkillTarget::Ally => use_on_ally(player_id, &skill, words, writer, state),
+    }
+}
+
+fn use_on_enemy(
+    player_id: Id<Player>,
+    skill: &Skill,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("use_skill: Self not found")?;
+    if game_zone::is_safe_room(player.room_id, state) {
+        writer.tell(player_id, Line::str("You cannot fight here."));
+        return Ok(());
+    }
+    let room = state.rooms.get(&player.room_id).ok_or("use_skill: Room not found")?;
+
+    // Skills only work on mobs. Players fighting each other have to use plain attacks.
+    let player_msg = format!("You cannot use {} on other players.", skill.name);
+    let target = if words.is_empty() {
+        match player.attack_target {
+            Some(AttackTarget::Mob(mob_id)) => state.mobs.by_id().get(&mob_id),
+            Some(AttackTarget::Player(_)) => {
+                writer.tell(player_id, Line::str(&player_msg));
+                return Ok(());
+            }
+            None => None,
+        }
+        .filter(|mob| mob.room_id == player.room_id)
+    } else {
+        let target_str = words.join(" ");
+        match resolve_targets_in_room(&target_str, room, &state.mobs).into_iter().next() {
+            Some(RoomTarget::Mob { mob }) => Some(mob),
+            Some(_) => {
+                let msg = format!("You can only use {} on enemies.", skill.name);
+                writer.tell(player_id, Line::str(&msg));
+                return Ok(());
+            }
+            None if state
+                .players
+                .by_name(&target_str)
+                .is_some_and(|other| other.room_id == player.room_id) =>
+            {
+                writer.tell(player_id, Line::str(&player_msg));
+                return Ok(());
+            }
+            None => None,
+        }
+    };
+    let Some(mob) = target else {
+        if words.is_empty() {
+            let msg = format!("Use {} on what?", skill.name);
+            writer.tell(player_id, Line::str(&msg));
+        } else {
+            writer.tell(player_id, Line::str("You do not see that here."));
+        }
+        return Ok(());
+    };
+    let (mob_id, mob_name, room_id) = (mob.id, mob.template.name.clone(), mob.room_id);
+    let damage = mob.template.damage_modifiers.apply(skill.damage, skill.damage_type);
+
+    let msg_self = format!("You use {} on the {}.", skill.name, mob_name);
+    writer.tell(player_id, span(&msg_self).color(Color::LightCyan).line());
+    let msg_others = format!("{} uses {} on the {}.", player.name, skill.name, mob_name);
+    spend(player_id, skill, state);
+    writer.tell_many(
+        state.players.ids_in_room_except(room_id, player_id),
+        span(&msg_others).color(Color::Cyan).line(),
+    );
+
+    if skill.damage > 0 {
+        let killed = state.mobs.modify(&mob_id, |mob| {
+            let killed = damage >= mob.hp;
+            if !killed {
+                mob.hp -= damage;
+            }
+            killed
+        });
+        let msg = format!(
+            "The {} takes {} {} damage.",
+            mob_name, damage, skill.damage_type
+        );
+        writer.tell_many(
+            state.players.ids_in_room(room_id),
+            span(&msg).color(Color::Cyan).line(),
+        );
+        if killed {
+            let msg = format!("The {} dies.", mob_name);
+            writer.tell_many(
+                state.players.ids_in_room(room_id),
+                span(&msg).color(Color::DarkGrey).line(),
+            );
+            game_combat::remove_mob(mob_id, state);
+            return Ok(());
+        }
+    }
+    if let Some(application) = &skill.status {
+        let now = state.ticks;
+        state.mobs.modify(&mob_id, |mob| mob.status_effects.apply(application, now));
+        let msg = format!("The {} is {}.", mob_name, application.status.adjective());
+        writer.tell_many(state.players.ids_in_room(room_id), Line::str(&msg));
+    }
+
+    state.players.modify(&player_id, |player| {
+        player.attack_target.get_or_insert(AttackTarget::Mob(mob_id));
+    });
+    game_combat::provoke(mob_id, player_id, writer, state);
+    Ok(())
+}
+
+fn use_on_ally(
+    player_id: Id<Player>,
+    skill: &Skill,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("use_skill: Self not found")?;
+    let room_id = player.room_id;
+    let target = if words.is_empty() {
+        Some(player)
+    } else {
+        state
+            .players
+            .by_name(&words.join(" "))
+            .filter(|target| target.room_id == room_id)
+    };
+    let Some(target) = target else {
+        writer.tell(player_id, Line::str("You do not see them here."));
+        return Ok(());
+    };
+    let (target_id, target_name) = (target.id, target.name.clone());
+
+    if target_id == player_id {
+        let msg_self = format!("You use {}.", skill.name);
+        writer.tell(player_id, span(&msg_self).color(Color::LightCyan).line());
+        let msg_others = format!("{} uses {}.", player.name, skill.name);
+        writer.tell_many(
+            state.players.ids_in_room_except(room_id, player_id),
+            span(&msg_others).color(Color::Cyan).line(),
+        );
+    } else {
+        let msg_self = format!("You use {} on {}.", skill.name, target_name);
+        writer.tell(player_id, span(&msg_self).color(Color::LightCyan).line());
+        let msg_target = format!("{} uses {} on you.", player.name, skill.name);
+        writer.tell(target_id, span(&msg_target).color(Color::LightCyan).line());
+        let msg_others = format!("{} uses {} on {}.", player.name, skill.name, target_name);
+        writer.tell_many(
+            state
+                .players
+                .ids_in_room_except(room_id, player_id)
+                .filter(|id| *id != target_id),
+            span(&msg_others).color(Color::Cyan).line(),
+        );
+    }
+    spend(player_id, skill, state);
+
+    if skill.heal > 0 {
+        let healed = state.players.modify(&target_id, |target| {
+            let healed = skill.heal.min(target.max_hp - target.hp);
+            target.hp += healed;
+            healed
+        });
+        let msg_target = format!("You recover {} HP.", healed);
+        writer.tell(target_id, span(&msg_target).color(Color::LightGreen).line());
+        let msg_others = format!("{} recovers {} HP.", target_name, healed);
+        writer.tell_many(
+            state.players.ids_in_room_except(room_id, target_id),
+            span(&msg_others).color(Color::Green).line(),
+        );
+    }
+    if let Some(application) = &skill.status {
+        let now = state.ticks;
+        state.players.modify(&target_id, |target| {
+            target.status_effects.apply(application, now)
+        });
+        let adjective = application.status.adjective();
+        let msg_target = format!("You are {}.", adjective);
+        writer.tell(target_id, span(&msg_target).color(Color::LightGreen).line());
+        let msg_others = format!("{} is {}.", target_name, adjective);
+        writer.tell_many(
+            state.players.ids_in_room_except(room_id, target_id),
+            span(&msg_others).color(Color::Green).line(),
+        );
+    }
+    Ok(())
+}
+
+fn is_ready(player: &Player, skill: &Skill, state: &GameState) -> bool {
+    player
+        .skill_ready_at
+        .get(&skill.name)
+        .is_none_or(|ready_at| *ready_at <= state.ticks)
+}
+
+fn spend(player_id: Id<Player>, skill: &Skill, state: &mut GameState) {
+    let ready_at = state.ticks + skill.cooldown;
+    state.players.modify(&player_id, |player| {
+        player.mana -= skill.mana_cost;
+        player.skill_ready_at.insert(skill.name.clone(), ready_at);
+    });
+}
+
+pub fn learn_skill(
+    player_id: Id<Player>,
+    skill_name: &str,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) {
+    let Some(skill) = state.skills.get(&skill_name.to_ascii_lowercase()) else {
+        warn!("learn_skill: No skill named {}", skill_name);
+        return;
+    };
+    let name = skill.name.clone();
+    let learned = state.players.modify(&player_id, |player| player.skills.insert(name.clone()));
+    if learned {
+        let msg = format!("You learn {}!", name);
+        writer.tell(player_id, span(&msg).color(Color::LightGreen).bold().line());
+    } else {
+        writer.tell(player_id, Line::str(&format!("You already know {}.", name)));
+    }
+}
+
+pub fn tick_mana(state: &mut GameState) {
+    if !state.ticks.is_on_division(*MANA_REGEN_FREQ, TickDuration::zero()) {
+        return;
+    }
+    let player_ids = state
+        .players
+        .by_id()
+        .values()
+        .filter(|player| player.mana < player.max_mana)
+        .map(|player| player.id)
+        .collect::<Vec<_>>();
+    for player_id in player_ids {
+        state.players.modify(&player_id, |p| {
+            p.mana = (p.mana + p.max_mana / 20).min(p.max_mana)
+        });
+    }
+}
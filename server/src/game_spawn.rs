@@ -0,0 +1,220 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    event_writer::EventWriter,
+    game_state::{GameState, Room},
+    id::Id,
+    line::Line,
+    mob::{Mob, MobSpawn, MobTemplate, SpawnOrigin},
+    status_effect::StatusEffects,
+};
+
+pub fn spawn_mob(
+    room_id: Id<Room>,
+    template: MobTemplate,
+    spawn_origin: Option<SpawnOrigin>,
+    state: &mut GameState,
+) -> Id<Mob> {
+    let id = state.mob_id_source.next();
+    let max_hp = template.max_hp.roll(&mut state.rng);
+    let damage = template.damage.roll(&mut state.rng);
+    let attack_offset = template.attack_period.random_offset(&mut state.rng);
+    state.mobs.insert(Mob {
+        id,
+        room_id,
+        spawn_origin,
+        summoner: None,
+        template,
+        hp: max_hp,
+        max_hp,
+        damage,
+        pursued_rooms: 0,
+        ability_ready_at: HashMap::new(),
+        pending_ability: None,
+        enraged: false,
+        status_effects: StatusEffects::default(),
+        attack_offset,
+        hostile_to: HashSet::new(),
+        attack_target: None,
+    });
+    id
+}
+
+/// Fills up every spawn of the room as on a zone reset.
+pub fn populate_room(room_id: Id<Room>, state: &mut GameState) {
+    let spawn_count = state.rooms.get(&room_id).map_or(0, |room| room.mob_spawns.len());
+    for index in 0..spawn_count {
+        populate_spawn(SpawnOrigin { room_id, index }, state);
+    }
+}
+
+/// Spawns a random number of mobs between the spawn's count and maximum count, minus the ones
+/// still around, and fills up the groups of the ones still around. Each mob is subject to the
+/// spawn's chance and hours.
+pub fn populate_spawn(origin: SpawnOrigin, state: &mut GameState) {
+    let Some(spawn) = find_spawn(origin, state).cloned() else {
+        return;
+    };
+    if !spawn.is_active_at(state.game_hour()) {
+        return;
+    }
+    let present = present_count(origin, spawn.mob_template_id, state);
+    let missing = spawn.roll_reset_count(&mut state.rng).saturating_sub(present);
+    let appearing = (0..missing).filter(|_| spawn.roll_chance(&mut state.rng)).count();
+    spawn_missing(origin, &spawn, present + appearing, state);
+}
+
+pub fn schedule_respawn(origin: SpawnOrigin, state: &mut GameState) {
+    if let Some(spawn) = find_spawn(origin, state) {
+        let respawn_at = state.ticks + spawn.respawn_delay();
+        state.scheduled_mob_spawns.entry(respawn_at).or_default().push(origin);
+    }
+}
+
+/// Replaces dead mobs whose respawn is due. If the spawn is outside its hours or the chance
+/// roll fails, it tries again after another respawn delay.
+pub fn tick_respawns(writer: &mut EventWriter, state: &mut GameState) {
+    let remaining = state.scheduled_mob_spawns.split_off(&(state.ticks.increase()));
+    let due = std::mem::replace(&mut state.scheduled_mob_spawns, remaining);
+
+    for origin in due.into_values().flatten() {
+        let Some(spawn) = find_spawn(origin, state).cloned() else {
+            continue;
+        };
+        if missing_templates(origin, &spawn, spawn.count, state).is_empty() {
+            continue;
+        }
+        if !spawn.is_active_at(state.game_hour()) || !spawn.roll_chance(&mut state.rng) {
+            schedule_respawn(origin, state);
+            continue;
+        }
+        for mob_id in spawn_missing(origin, &spawn, spawn.count, state) {
+            if let Some(mob) = state.mobs.by_id().get(&mob_id) {
+                writer.tell_many(
+                    state.players.ids_in_room(mob.room_id),
+                    Line::str(&format!("A {} appears.", mob.template.name)),
+                );
+            }
+        }
+    }
+}
+
+/// Spawns whatever is missing from the given number of groups, each made of a mob of the spawn
+/// and the mobs of its group.
+fn spawn_missing(
+    origin: SpawnOrigin,
+    spawn: &MobSpawn,
+    group_count: usize,
+    state: &mut GameState,
+) -> Vec<Id<Mob>> {
+    let templates = missing_templates(origin, spawn, group_count, state)
+        .into_iter()
+        .filter_map(|template_id| state.mob_templates.get(&template_id).cloned())
+        .collect::<Vec<_>>();
+    templates
+        .into_iter()
+        .map(|template| spawn_mob(origin.room_id, template, Some(origin), state))
+        .collect()
+}
+
+/// The templates of the mobs missing from the given number of groups, counting the mobs of each
+/// template that are still around.
+fn missing_templates(
+    origin: SpawnOrigin,
+    spawn: &MobSpawn,
+    group_count: usize,
+    state: &GameState,
+) -> Vec<Id<MobTemplate>> {
+    let mut present = HashMap::new();
+    for mob in state.mobs.by_id().values() {
+        if mob.spawn_origin == Some(origin) {
+            *present.entry(mob.template.id).or_insert(0) += 1;
+        }
+    }
+    let group = std::iter::once(spawn.mob_template_id).chain(spawn.group.iter().copied());
+    group
+        .cycle()
+        .take(group_count * (spawn.group.len() + 1))
+        .filter(|template_id| match present.get_mut(template_id) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+fn find_spawn(origin: SpawnOrigin, state: &GameState) -> Option<&MobSpawn> {
+    state
+        .rooms
+        .get(&origin.room_id)
+        .and_then(|room| room.mob_spawns.get(origin.index))
+}
+
+/// How many mobs of the template are around from the spawn.
+fn present_count(origin: SpawnOrigin, template_id: Id<MobTemplate>, state: &GameState) -> usize {
+    state
+        .mobs
+        .by_id()
+        .values()
+        .filter(|mob| mob.spawn_origin == Some(origin) && mob.template.id == template_id)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game_combat, test_util, tick::TickDuration};
+
+    static ROOMS: &str = "
+- id: 0
+  name: Den
+  zoneId: 0
+  description: A wolf den.
+  exits: {}
+  mobSpawns:
+  - { mobTemplateId: 0, respawnSecs: 1, group: [1, 1] }
+";
+    static MOB_TEMPLATES: &str = "
+- { id: 0, name: wolf, description: A wolf., maxHp: [10, 10], damage: [2, 2], attackPeriod: 2 }
+- { id: 1, name: cub, description: A cub., maxHp: [5, 5], damage: [1, 1], attackPeriod: 2 }
+";
+
+    fn count_templates(state: &GameState) -> (usize, usize) {
+        let mobs = state.mobs.by_id().values();
+        let wolves = mobs.clone().filter(|mob| mob.template.id == Id::new(0)).count();
+        let cubs = mobs.filter(|mob| mob.template.id == Id::new(1)).count();
+        (wolves, cubs)
+    }
+
+    fn kill_and_respawn(template_id: Id<MobTemplate>, state: &mut GameState) {
+        let mob_id = state
+            .mobs
+            .by_id()
+            .values()
+            .find(|mob| mob.template.id == template_id)
+            .map(|mob| mob.id)
+            .unwrap();
+        game_combat::remove_mob(mob_id, state);
+        state.ticks = state.ticks + TickDuration::from_secs(1.0);
+        tick_respawns(&mut EventWriter::new(), state);
+    }
+
+    #[test]
+    fn groups_respawn_only_their_missing_mobs() {
+        let mut state = test_util::game_state(ROOMS, MOB_TEMPLATES);
+        populate_room(Id::new(0), &mut state);
+        assert_eq!(count_templates(&state), (1, 2));
+
+        for _ in 0..5 {
+            kill_and_respawn(Id::new(0), &mut state);
+            assert_eq!(count_templates(&state), (1, 2));
+        }
+        kill_and_respawn(Id::new(1), &mut state);
+        assert_eq!(count_templates(&state), (1, 2));
+
+        populate_room(Id::new(0), &mut state);
+        assert_eq!(count_templates(&state), (1, 2));
+    }
+}
@@ -1,55 +1,136 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
-use serde::Deserialize;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    account::{Account, Role},
+    config::Config,
+    game_chat::ChatConfig,
     id::{Id, IdMap, IdSource},
-    mob::{Mob, MobSpawn, MobTemplate},
+    mob::{Mob, MobSpawn, MobTemplate, SpawnOrigin},
     mob_coll::MobColl,
     named::Named,
+    player::Player,
     player_coll::PlayerColl,
+    skill::Skill,
+    status_effect::StatusApplication,
     tick::Tick,
+    zone::Zone,
 };
 
 pub struct LoadedGameState {
+    pub zones: IdMap<Zone>,
     pub rooms: IdMap<Room>,
     pub mob_templates: IdMap<MobTemplate>,
+    pub skills: Vec<Skill>,
+    pub chat_config: ChatConfig,
+    pub accounts: Vec<Account>,
+    pub config: Config,
 }
 
 #[derive(Clone, Debug)]
 pub struct GameState {
     pub ticks: Tick,
+    pub config: Config,
     pub players: PlayerColl,
+    pub zones: IdMap<Zone>,
     pub rooms: IdMap<Room>,
     pub room_vars: HashMap<(Id<Room>, String), i32>,
+    /// Increased whenever rooms or their variables change, which may change what maps show.
+    pub map_version: u64,
     pub scheduled_room_var_resets: BTreeMap<Tick, (Id<Room>, String, String)>,
     pub mob_templates: IdMap<MobTemplate>,
     pub mobs: MobColl,
     pub mob_id_source: IdSource<Mob>,
-    pub scheduled_mob_spawns: BTreeMap<Tick, (Id<Room>, Id<MobTemplate>)>,
+    pub scheduled_mob_spawns: BTreeMap<Tick, Vec<SpawnOrigin>>,
+    pub zone_resets: HashMap<Id<Zone>, Tick>,
+    pub skills: BTreeMap<String, Skill>,
+    pub chat_config: ChatConfig,
+    pub mutes: HashMap<String, Option<Tick>>,
+    pub accounts: HashMap<String, Account>,
+    pub banned: HashSet<String>,
+    pub rng: StdRng,
 }
 
 impl GameState {
     pub fn new(loaded_game_state: LoadedGameState) -> GameState {
-        let LoadedGameState { rooms, mob_templates } = loaded_game_state;
+        let LoadedGameState {
+            zones,
+            rooms,
+            mob_templates,
+            skills,
+            chat_config,
+            accounts,
+            config,
+        } = loaded_game_state;
+        let rng = config.rng_seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
         GameState {
+            config,
+            zones,
             rooms,
             mob_templates,
+            chat_config,
             ticks: Tick::zero(),
             players: PlayerColl::new(),
             room_vars: HashMap::new(),
+            map_version: 0,
             scheduled_room_var_resets: BTreeMap::new(),
             mobs: MobColl::new(),
             mob_id_source: IdSource::new(0),
             scheduled_mob_spawns: BTreeMap::new(),
+            zone_resets: HashMap::new(),
+            skills: skills
+                .into_iter()
+                .map(|skill| (skill.name.to_ascii_lowercase(), skill))
+                .collect(),
+            mutes: HashMap::new(),
+            accounts: accounts
+                .into_iter()
+                .map(|account| (account.name.to_ascii_lowercase(), account))
+                .collect(),
+            banned: HashSet::new(),
+            rng,
         }
     }
 
+    pub fn account_role(&self, player_name: &str) -> Role {
+        self.accounts
+            .get(&player_name.to_ascii_lowercase())
+            .map_or(Role::Player, |account| account.role)
+    }
+
+    pub fn is_banned(&self, player_name: &str) -> bool {
+        self.banned.contains(&player_name.to_ascii_lowercase())
+    }
+
+    pub fn zone_of(&self, room_id: Id<Room>) -> Option<&Zone> {
+        self.rooms.get(&room_id).and_then(|room| self.zones.get(&room.zone_id))
+    }
+
+    pub fn player_ids_in_zone(&self, zone_id: Id<Zone>) -> Vec<Id<Player>> {
+        self.players
+            .by_id()
+            .values()
+            .filter(|player| {
+                self.rooms.get(&player.room_id).is_some_and(|room| room.zone_id == zone_id)
+            })
+            .map(|player| player.id)
+            .collect()
+    }
+
+    /// The hour of the in-game day, from 0 to 23.
+    pub fn game_hour(&self) -> u32 {
+        let hours = self.ticks.as_secs() / self.config.game_hour_secs;
+        (hours as u64 % 24) as u32
+    }
+
     pub fn get_room_var(&self, room_id: Id<Room>, var: String) -> i32 {
         *self.room_vars.get(&(room_id, var)).unwrap_or(&0)
     }
 
     pub fn set_room_var(&mut self, room_id: Id<Room>, var: String, value: i32) {
+        self.map_version += 1;
         if value == 0 {
             self.room_vars.remove(&(room_id, var));
         } else {
@@ -58,17 +139,36 @@ impl GameState {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Room {
     pub id: Id<Room>,
     pub name: String,
+    pub zone_id: Id<Zone>,
     pub description: RoomDescription,
-    pub exits: HashMap<String, RoomExit>,
-    #[serde(default)]
+    pub exits: BTreeMap<String, RoomExit>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub objects: Vec<RoomObject>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mob_spawns: Vec<MobSpawn>,
+    /// Position on the map as `[x, y]`, with y growing southwards. Rooms without coordinates
+    /// are laid out from the compass directions of their exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coords: Option<(i32, i32)>,
+    /// Values the room vars start with and return to when the zone resets.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, i32>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub flags: BTreeSet<RoomFlag>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum RoomFlag {
+    /// Nobody can start a fight here, even if the zone allows it.
+    Safe,
+    /// Players can fight each other here without opting in.
+    Pvp,
 }
 
 impl Room {
@@ -81,21 +181,21 @@ impl Room {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(untagged, rename_all = "camelCase")]
 pub enum RoomExit {
     Static(Id<Room>),
     Conditional { condition: Condition, to: Id<Room> },
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RoomObject {
     pub name: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub aliases: Vec<String>,
     pub description: RoomDescription,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub commands: Vec<RoomCommand>,
 }
 
@@ -109,37 +209,38 @@ impl Named for RoomObject {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum RoomDescription {
     Static(String),
     Dynamic(Vec<DynamicDescriptionFragment>),
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DynamicDescriptionFragment {
     pub fragment: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<Condition>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RoomCommand {
     pub command: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub condition: Option<Condition>,
     pub statements: Vec<Statement>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Condition {
     Equals(String, i32),
     NotEquals(String, i32),
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Statement {
     SetRoomVar(String, i32),
@@ -147,4 +248,7 @@ pub enum Statement {
     TellSelf(String),
     TellOthers(String),
     TellRoom(String),
+    LearnSkill(String),
+    /// Puts a status effect on the player running the command.
+    ApplyStatus(StatusApplication),
 }
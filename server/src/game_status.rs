@@ -0,0 +1,156 @@
+use once_cell::sync::Lazy;
+
+use crate::{
+    event_writer::EventWriter,
+    game_combat,
+    game_state::GameState,
+    line::{span, Color, Line},
+    status_effect::{StatusEffects, StatusKind},
+    text_util::and_spans,
+    tick::{Tick, TickDuration},
+};
+
+pub static STATUS_TICK_FREQ: Lazy<TickDuration> = Lazy::new(|| TickDuration::from_secs(1.0));
+
+/// Removes the status effects that ran out, and applies poison and regeneration every second.
+pub fn tick_status_effects(writer: &mut EventWriter, state: &mut GameState) {
+    expire_effects(writer, state);
+    if state.ticks.is_on_division(*STATUS_TICK_FREQ, TickDuration::zero()) {
+        tick_player_effects(writer, state);
+        tick_mob_effects(writer, state);
+    }
+}
+
+/// Describes the status effects as in "You are poisoned (4s) and blessed (20s).", or nothing if
+/// there are none.
+pub fn describe_effects(
+    subject: &str,
+    effects: &StatusEffects,
+    now: Tick,
+    with_durations: bool,
+) -> Option<Line> {
+    let spans = effects
+        .iter()
+        .filter(|(_, active)| now < active.until)
+        .map(|(kind, active)| {
+            let text = if with_durations {
+                let secs_left = (active.until.as_secs() - now.as_secs()).ceil();
+                format!("{} ({}s)", kind.adjective(), secs_left)
+            } else {
+                kind.adjective().to_string()
+            };
+            span(&text).color(effect_color(kind))
+        })
+        .collect::<Vec<_>>();
+    if spans.is_empty() {
+        None
+    } else {
+        Some(Line::str(&format!("{} ", subject)).extend(and_spans(spans)).push(span(".")))
+    }
+}
+
+fn effect_color(kind: StatusKind) -> Color {
+    match kind {
+        StatusKind::Poison | StatusKind::Stun => Color::LightRed,
+        StatusKind::Regeneration | StatusKind::Haste | StatusKind::Blessing => Color::LightGreen,
+    }
+}
+
+fn expire_effects(writer: &mut EventWriter, state: &mut GameState) {
+    let now = state.ticks;
+    let player_ids = state
+        .players
+        .by_id()
+        .values()
+        .filter(|player| player.status_effects.has_expired(now))
+        .map(|player| player.id)
+        .collect::<Vec<_>>();
+    for player_id in player_ids {
+        let expired = state.players.modify(&player_id, |p| p.status_effects.expire(now));
+        for kind in expired {
+            writer.tell(
+                player_id,
+                span(kind.wear_off_message()).color(Color::DarkGrey).line(),
+            );
+        }
+    }
+
+    let mob_ids = state
+        .mobs
+        .by_id()
+        .values()
+        .filter(|mob| mob.status_effects.has_expired(now))
+        .map(|mob| mob.id)
+        .collect::<Vec<_>>();
+    for mob_id in mob_ids {
+        state.mobs.modify(&mob_id, |mob| mob.status_effects.expire(now));
+    }
+}
+
+fn tick_player_effects(writer: &mut EventWriter, state: &mut GameState) {
+    let now = state.ticks;
+    let affected = state
+        .players
+        .by_id()
+        .values()
+        .map(|player| {
+            let effects = &player.status_effects;
+            (
+                player.id,
+                effects.potency(StatusKind::Poison, now),
+                effects.potency(StatusKind::Regeneration, now),
+            )
+        })
+        .filter(|(_, poison, regeneration)| *poison > 0 || *regeneration > 0)
+        .collect::<Vec<_>>();
+
+    for (player_id, poison, regeneration) in affected {
+        if regeneration > 0 {
+            state.players.modify(&player_id, |p| p.hp = (p.hp + regeneration).min(p.max_hp));
+        }
+        if poison > 0 {
+            let msg = format!("The poison burns you for {} damage.", poison);
+            writer.tell(player_id, span(&msg).color(Color::LightRed).line());
+            if game_combat::damage_player(player_id, poison, &mut state.players, writer) {
+                game_combat::respawn_player(player_id, writer, state);
+            }
+        }
+    }
+}
+
+fn tick_mob_effects(writer: &mut EventWriter, state: &mut GameState) {
+    let now = state.ticks;
+    let affected = state
+        .mobs
+        .by_id()
+        .values()
+        .map(|mob| {
+            let effects = &mob.status_effects;
+            (
+                mob.id,
+                effects.potency(StatusKind::Poison, now),
+                effects.potency(StatusKind::Regeneration, now),
+            )
+        })
+        .filter(|(_, poison, regeneration)| *poison > 0 || *regeneration > 0)
+        .collect::<Vec<_>>();
+
+    for (mob_id, poison, regeneration) in affected {
+        let (killed, room_id, name) = state.mobs.modify(&mob_id, |mob| {
+            mob.hp = (mob.hp + regeneration).min(mob.max_hp);
+            let killed = poison >= mob.hp;
+            if !killed {
+                mob.hp -= poison;
+            }
+            (killed, mob.room_id, mob.template.name.clone())
+        });
+        if killed {
+            let msg = format!("The {} succumbs to the poison.", name);
+            writer.tell_many(
+                state.players.ids_in_room(room_id),
+                span(&msg).color(Color::DarkGrey).line(),
+            );
+            game_combat::remove_mob(mob_id, state);
+        }
+    }
+}
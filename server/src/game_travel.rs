@@ -0,0 +1,126 @@
+use log::warn;
+
+use crate::{
+    event_writer::EventWriter,
+    game_combat::is_in_combat,
+    game_logic::move_self,
+    game_room::{find_path, open_exits},
+    game_state::GameState,
+    id::Id,
+    line::Line,
+    player::Player,
+};
+
+pub fn travel(
+    player_id: Id<Player>,
+    words: Vec<&str>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("travel: Self not found")?;
+    let room_name = words.join(" ");
+
+    let current_room = state.rooms.get(&player.room_id).ok_or("travel: Room not found")?;
+    if current_room.name.eq_ignore_ascii_case(&room_name) {
+        writer.tell(player_id, Line::str("You are already there."));
+        return Ok(());
+    }
+    if is_in_combat(player, state) {
+        writer.tell(player_id, Line::str("You cannot travel while fighting."));
+        return Ok(());
+    }
+
+    let path = find_path(
+        player.room_id,
+        state,
+        |room_id| player.has_visited(room_id),
+        |room| {
+            room.name.eq_ignore_ascii_case(&room_name)
+                || room.name.to_ascii_lowercase().starts_with(&room_name.to_ascii_lowercase())
+        },
+    );
+    match path {
+        Some(path) => {
+            let destination = path.last().and_then(|room_id| state.rooms.get(room_id));
+            let destination_name = destination.map_or("???", |room| &room.name);
+            writer.tell(
+                player_id,
+                Line::str(&format!("You set off towards {}.", destination_name)),
+            );
+            state.players.modify(&player_id, |player| player.travel_path = path.into());
+        }
+        None => writer.tell(
+            player_id,
+            Line::str("You do not know the way to such a place."),
+        ),
+    }
+    Ok(())
+}
+
+/// Moves each travelling player one room further along their path.
+pub fn tick_travel(writer: &mut EventWriter, state: &mut GameState) {
+    let travelling = state
+        .players
+        .by_id()
+        .values()
+        .filter(|player| !player.travel_path.is_empty() && !player.is_stunned(state.ticks))
+        .map(|player| player.id)
+        .collect::<Vec<_>>();
+
+    for player_id in travelling {
+        let Some(player) = state.players.by_id().get(&player_id) else {
+            continue;
+        };
+        if is_in_combat(player, state) {
+            cancel_travel(
+                player_id,
+                "Your travel is interrupted by the fight.",
+                writer,
+                state,
+            );
+            continue;
+        }
+
+        let next_room_id = player.travel_path.front().copied();
+        let direction = next_room_id.and_then(|next_room_id| {
+            state.rooms.get(&player.room_id).and_then(|room| {
+                open_exits(room, state)
+                    .find(|(_, to)| *to == next_room_id)
+                    .map(|(direction, _)| direction.to_string())
+            })
+        });
+        match (next_room_id, direction) {
+            (Some(next_room_id), Some(direction)) => {
+                state.players.modify(&player_id, |player| player.travel_path.pop_front());
+                if let Err(err) = move_self(player_id, next_room_id, &direction, writer, state) {
+                    warn!("Travel: {}", err);
+                }
+                let arrived = state
+                    .players
+                    .by_id()
+                    .get(&player_id)
+                    .is_some_and(|player| player.travel_path.is_empty());
+                if arrived {
+                    writer.tell(player_id, Line::str("You have arrived."));
+                }
+            }
+            _ => cancel_travel(player_id, "The way ahead is blocked.", writer, state),
+        }
+    }
+}
+
+pub fn cancel_travel(
+    player_id: Id<Player>,
+    message: &str,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) {
+    let was_travelling = state.players.modify(&player_id, |player| {
+        let was_travelling = !player.travel_path.is_empty();
+        player.travel_path.clear();
+        was_travelling
+    });
+    if was_travelling {
+        writer.tell(player_id, Line::str(message));
+    }
+}
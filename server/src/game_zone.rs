@@ -0,0 +1,183 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    event_writer::EventWriter,
+    game_admin::audit,
+    game_spawn,
+    game_state::{GameState, Room, RoomFlag},
+    id::Id,
+    line::{span, Color, Line},
+    player::Player,
+    zone::{ResetMode, Zone, ZoneFlag},
+};
+
+static DEFAULT_RESPAWN_ROOM_ID: Id<Room> = Id::new(0);
+static DEFAULT_RESET_MESSAGE: &str = "You feel the area around you settle back into its old ways.";
+
+pub fn zone(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &GameState,
+) -> Result<(), String> {
+    let player = state.players.by_id().get(&player_id).ok_or("zone: Self not found")?;
+    let Some(zone) = state.zone_of(player.room_id) else {
+        writer.tell(player_id, Line::str("You are nowhere in particular."));
+        return Ok(());
+    };
+
+    let mut lines = vec![Line::str("You are in ").push(span(&zone.name).bold()).push(span("."))];
+    if let Some((min, max)) = zone.level_range {
+        lines.push(Line::str(&format!(
+            "It is meant for levels {} to {}.",
+            min, max
+        )));
+    }
+    if zone.has_flag(ZoneFlag::Safe) {
+        lines.push(Line::str("It is a safe place, nobody can fight here."));
+    } else if zone.has_flag(ZoneFlag::Pvp) {
+        lines.push(Line::str("Players can fight each other here."));
+    }
+    if let Some(interval) = zone.reset_interval {
+        let minutes = (interval.as_secs() / 60.0).round();
+        lines.push(Line::str(&format!("It resets every {} minutes.", minutes)));
+    }
+    writer.tell_lines(player_id, &lines);
+    Ok(())
+}
+
+/// Now and then shows one of its ambient messages to everyone in a zone.
+pub fn tick_ambient_messages(writer: &mut EventWriter, state: &mut GameState) {
+    let GameState { zones, config, rng, .. } = state;
+    let mut messages = Vec::new();
+    for zone in zones.values() {
+        if !rng.gen_bool(config.ambient_message_chance) {
+            continue;
+        }
+        if let Some(message) = zone.ambient_messages.choose(rng) {
+            messages.push((zone.id, message.clone()));
+        }
+    }
+    for (zone_id, message) in messages {
+        writer.tell_many(
+            state.player_ids_in_zone(zone_id).into_iter(),
+            span(&message).color(Color::DarkGrey).line(),
+        );
+    }
+}
+
+pub fn reset_zone_command(
+    player_id: Id<Player>,
+    writer: &mut EventWriter,
+    state: &mut GameState,
+) -> Result<(), String> {
+    let room_id = state.players.by_id().get(&player_id).ok_or("zreset: Self not found")?.room_id;
+    let zone_id = state.zone_of(room_id).ok_or("zreset: Zone not found")?.id;
+    reset_zone(zone_id, writer, state);
+    audit(
+        player_id,
+        &format!("zreset {}", zone_id.value),
+        writer,
+        state,
+    );
+    Ok(())
+}
+
+pub fn schedule_resets(state: &mut GameState) {
+    state.zone_resets = state
+        .zones
+        .values()
+        .filter_map(|zone| zone.reset_interval.map(|interval| (zone.id, state.ticks + interval)))
+        .collect();
+}
+
+/// Resets the zones that are due, except the ones that wait for their players to leave.
+pub fn tick_zone_resets(writer: &mut EventWriter, state: &mut GameState) {
+    let due = state
+        .zone_resets
+        .iter()
+        .filter(|(_, reset_at)| **reset_at <= state.ticks)
+        .filter_map(|(zone_id, _)| state.zones.get(zone_id))
+        .filter(|zone| {
+            zone.reset_mode == ResetMode::Always || state.player_ids_in_zone(zone.id).is_empty()
+        })
+        .map(|zone| zone.id)
+        .collect::<Vec<_>>();
+    for zone_id in due {
+        reset_zone(zone_id, writer, state);
+    }
+}
+
+/// Restores the default room vars and tops up the mob populations of every room in the zone.
+pub fn reset_zone(zone_id: Id<Zone>, writer: &mut EventWriter, state: &mut GameState) {
+    let room_ids = state
+        .rooms
+        .values()
+        .filter(|room| room.zone_id == zone_id)
+        .map(|room| room.id)
+        .collect::<Vec<_>>();
+    for room_id in room_ids {
+        reset_room(room_id, state);
+    }
+
+    if let Some(zone) = state.zones.get(&zone_id) {
+        if let Some(interval) = zone.reset_interval {
+            state.zone_resets.insert(zone_id, state.ticks + interval);
+        }
+        let message = zone.reset_message.as_deref().unwrap_or(DEFAULT_RESET_MESSAGE);
+        writer.tell_many(
+            state.player_ids_in_zone(zone_id).into_iter(),
+            span(message).color(Color::DarkGrey).line(),
+        );
+    }
+}
+
+pub fn reset_room(room_id: Id<Room>, state: &mut GameState) {
+    let Some(room) = state.rooms.get(&room_id) else {
+        return;
+    };
+    let vars = room.vars.clone();
+
+    state.room_vars.retain(|(var_room_id, _), _| *var_room_id != room_id);
+    state.map_version += 1;
+    state
+        .scheduled_room_var_resets
+        .retain(|_, (var_room_id, _, _)| *var_room_id != room_id);
+    for (var, value) in vars {
+        state.set_room_var(room_id, var, value);
+    }
+    game_spawn::populate_room(room_id, state);
+}
+
+pub fn is_safe_room(room_id: Id<Room>, state: &GameState) -> bool {
+    state
+        .rooms
+        .get(&room_id)
+        .is_some_and(|room| room.flags.contains(&RoomFlag::Safe))
+        || state.zone_of(room_id).is_some_and(|zone| zone.has_flag(ZoneFlag::Safe))
+}
+
+/// Whether players can fight each other in the room without opting in.
+pub fn is_pvp_room(room_id: Id<Room>, state: &GameState) -> bool {
+    state
+        .rooms
+        .get(&room_id)
+        .is_some_and(|room| room.flags.contains(&RoomFlag::Pvp))
+        || state.zone_of(room_id).is_some_and(|zone| zone.has_flag(ZoneFlag::Pvp))
+}
+
+/// Where a player who dies in the given room comes back to life.
+pub fn respawn_room_id(room_id: Id<Room>, state: &GameState) -> Id<Room> {
+    state
+        .zone_of(room_id)
+        .map(|zone| zone.respawn_room_id)
+        .filter(|respawn_room_id| state.rooms.contains_key(respawn_room_id))
+        .unwrap_or(DEFAULT_RESPAWN_ROOM_ID)
+}
+
+/// The zone name shown next to a player in `who`, unless the zone hides it.
+pub fn whereabouts(player: &Player, state: &GameState) -> Option<String> {
+    state
+        .zone_of(player.room_id)
+        .filter(|zone| !zone.has_flag(ZoneFlag::Hidden))
+        .map(|zone| zone.name.clone())
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use std::marker::PhantomData;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub struct Id<T> {
@@ -13,14 +13,14 @@ pub struct Id<T> {
 pub type IdMap<T> = HashMap<Id<T>, T>;
 
 impl<T> Id<T> {
-    pub fn new(value: u64) -> Id<T> {
+    pub const fn new(value: u64) -> Id<T> {
         Id { value, phantom: PhantomData }
     }
 }
 
 impl<T> Clone for Id<T> {
     fn clone(&self) -> Self {
-        Self { value: self.value, phantom: self.phantom }
+        *self
     }
 }
 
@@ -32,9 +32,7 @@ impl<T> PartialEq for Id<T> {
     }
 }
 
-impl<T> Eq for Id<T> {
-    fn assert_receiver_is_total_eq(&self) {}
-}
+impl<T> Eq for Id<T> {}
 
 impl<T> Hash for Id<T> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -51,6 +49,15 @@ impl<'de, T> Deserialize<'de> for Id<T> {
     }
 }
 
+impl<T> Serialize for Id<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
 #[derive(Debug)]
 pub struct IdSource<T> {
     next_id: u64,
@@ -64,6 +64,8 @@ pub enum Color {
     Red,
     LightCyan,
     Cyan,
+    LightGreen,
+    Green,
 }
 
 impl Color {
@@ -78,6 +80,8 @@ impl Color {
             Color::Red => "red",
             Color::LightCyan => "light-cyan",
             Color::Cyan => "cyan",
+            Color::LightGreen => "light-green",
+            Color::Green => "green",
         }
     }
 }
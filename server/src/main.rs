@@ -1,11 +1,29 @@
+mod account;
+mod auth;
+mod character;
+mod config;
+mod damage;
 mod event_writer;
+mod file_io;
+mod game_ability;
+mod game_admin;
 mod game_alias;
+mod game_build;
 mod game_chat;
 mod game_combat;
+mod game_command;
+mod game_consider;
 mod game_help;
 mod game_logic;
+mod game_map;
+mod game_queue;
 mod game_room;
+mod game_skill;
+mod game_spawn;
 mod game_state;
+mod game_status;
+mod game_travel;
+mod game_zone;
 mod id;
 mod line;
 mod mob;
@@ -15,26 +33,48 @@ mod player;
 mod player_coll;
 mod server_actor;
 mod server_websocket;
+mod skill;
+mod status_effect;
+#[cfg(test)]
+mod test_util;
 mod text_util;
 mod tick;
+mod zone;
 
-use std::{fs, net::SocketAddr};
+use std::{fs, net::SocketAddr, sync::Arc};
 
+use account::Account;
+use auth::Authenticator;
 use game_state::{LoadedGameState, Room};
 use id::{Id, IdMap};
-use mob::MobTemplate;
 use serde::de::DeserializeOwned;
 use server_websocket::{handle_connection, ConnectQuery};
 use tokio::sync::mpsc;
 use warp::Filter;
+use zone::Zone;
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, command, password] = args.as_slice() {
+        if command == "hash-password" {
+            println!("{}", auth::hash_password(password).unwrap());
+            return;
+        }
+    }
+
+    let accounts: Vec<Account> = load_yaml("data/accounts.yaml");
+    let authenticator = Arc::new(Authenticator::new(&accounts));
     let loaded_game_state = LoadedGameState {
-        rooms: load_id_map("data/rooms.yaml", |r: &Room| r.id),
-        mob_templates: load_id_map("data/mob_templates.yaml", |mt: &MobTemplate| mt.id),
+        zones: load_id_map("data/zones.yaml", |z: &Zone| z.id),
+        rooms: load_id_map(game_build::ROOMS_PATH, |r: &Room| r.id),
+        mob_templates: mob::resolve_templates(load_yaml("data/mob_templates.yaml")).unwrap(),
+        skills: load_yaml("data/skills.yaml"),
+        chat_config: load_yaml("data/chat.yaml"),
+        accounts,
+        config: load_yaml("data/config.yaml"),
     };
 
     let socket_address = std::env::var("MUD_ADDR")
@@ -42,11 +82,21 @@ async fn main() {
         .and_then(|str| str.parse::<SocketAddr>().ok())
         .unwrap_or_else(|| ([127, 0, 0, 1], 8081).into());
 
+    let file_io = file_io::spawn();
     let (actor_sender, actor_receiver) = mpsc::channel::<server_actor::Message>(4096);
     {
         let actor_sender = actor_sender.clone();
+        let authenticator = authenticator.clone();
+        let file_io = file_io.clone();
         tokio::spawn(async move {
-            server_actor::run(actor_receiver, actor_sender, loaded_game_state).await
+            server_actor::run(
+                actor_receiver,
+                actor_sender,
+                loaded_game_state,
+                authenticator,
+                file_io,
+            )
+            .await
         });
     }
 
@@ -55,7 +105,11 @@ async fn main() {
         .and(warp::ws())
         .map(move |query: ConnectQuery, ws: warp::ws::Ws| {
             let message_sender = actor_sender.clone();
-            ws.on_upgrade(|websocket| handle_connection(websocket, query, message_sender))
+            let authenticator = authenticator.clone();
+            let file_io = file_io.clone();
+            ws.on_upgrade(|websocket| {
+                handle_connection(websocket, query, message_sender, authenticator, file_io)
+            })
         });
 
     warp::serve(routes).run(socket_address).await;
@@ -65,6 +119,13 @@ fn load_id_map<T>(path: &str, to_id: impl Fn(&T) -> Id<T>) -> IdMap<T>
 where
     T: DeserializeOwned,
 {
-    let list: Vec<T> = serde_yaml::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let list: Vec<T> = load_yaml(path);
     list.into_iter().map(|item| (to_id(&item), item)).collect()
 }
+
+fn load_yaml<T>(path: &str) -> T
+where
+    T: DeserializeOwned,
+{
+    serde_yaml::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
@@ -1,13 +1,97 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use serde::Deserialize;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
 
-use crate::{game_state::Room, id::Id, named::Named, player::Player, tick::TickDuration};
+use crate::{
+    damage::{DamageModifiers, DamageType},
+    game_state::Room,
+    id::{Id, IdMap},
+    named::Named,
+    player::Player,
+    status_effect::{StatusApplication, StatusEffects, StatusKind},
+    tick::{Tick, TickDuration},
+};
 
-#[derive(Clone, Debug, Deserialize)]
+static DEFAULT_RESPAWN_SECS: f32 = 30.0;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MobSpawn {
     pub mob_template_id: Id<MobTemplate>,
+    /// How many mobs the spawn keeps around. Dead ones are replaced up to this count.
+    #[serde(
+        default = "MobSpawn::default_count",
+        skip_serializing_if = "MobSpawn::is_default_count"
+    )]
+    pub count: usize,
+    /// When the zone resets, a random number of mobs between `count` and this are spawned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub respawn_secs: Option<f32>,
+    /// The chance of a mob actually appearing when it is due.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chance: Option<f64>,
+    /// The game hours from and until which the mobs appear, e.g. `[20, 6]` for the night.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<(u32, u32)>,
+    /// Other mobs that appear together with each mob of this spawn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group: Vec<Id<MobTemplate>>,
+}
+
+impl MobSpawn {
+    pub fn new(mob_template_id: Id<MobTemplate>) -> MobSpawn {
+        MobSpawn {
+            mob_template_id,
+            count: 1,
+            max_count: None,
+            respawn_secs: None,
+            chance: None,
+            hours: None,
+            group: vec![],
+        }
+    }
+
+    fn default_count() -> usize {
+        1
+    }
+
+    fn is_default_count(count: &usize) -> bool {
+        *count == 1
+    }
+
+    pub fn respawn_delay(&self) -> TickDuration {
+        TickDuration::from_secs(self.respawn_secs.unwrap_or(DEFAULT_RESPAWN_SECS))
+    }
+
+    pub fn roll_reset_count(&self, rng: &mut impl Rng) -> usize {
+        match self.max_count {
+            Some(max_count) if max_count > self.count => rng.gen_range(self.count..=max_count),
+            _ => self.count,
+        }
+    }
+
+    pub fn roll_chance(&self, rng: &mut impl Rng) -> bool {
+        self.chance.is_none_or(|chance| rng.gen_bool(chance.clamp(0.0, 1.0)))
+    }
+
+    pub fn is_active_at(&self, hour: u32) -> bool {
+        match self.hours {
+            Some((from, until)) if from <= until => (from..until).contains(&hour),
+            Some((from, until)) => hour >= from || hour < until,
+            None => true,
+        }
+    }
+}
+
+/// Which of a room's spawns a mob belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpawnOrigin {
+    pub room_id: Id<Room>,
+    pub index: usize,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -18,10 +102,102 @@ pub struct MobTemplate {
     #[serde(default)]
     pub aliases: Vec<String>,
     pub description: String,
-    pub max_hp: i32,
-    pub damage: i32,
+    pub max_hp: StatRange,
+    pub damage: StatRange,
     #[serde(deserialize_with = "TickDuration::deserialize_from_secs")]
     pub attack_period: TickDuration,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// Added to the mob's attack rolls.
+    #[serde(default)]
+    pub accuracy: i32,
+    /// Subtracted from the attack rolls against the mob.
+    #[serde(default)]
+    pub defense: i32,
+    #[serde(default, flatten)]
+    pub damage_modifiers: DamageModifiers,
+    /// Mobs of the same faction join each other's fights.
+    #[serde(default)]
+    pub faction: Option<String>,
+    /// Stays out of fights even when a mob of its faction is attacked.
+    #[serde(default)]
+    pub ignores_fights: bool,
+    /// When attacked, calls the mobs of its faction from the neighbouring rooms to its aid.
+    #[serde(default)]
+    pub calls_for_help: bool,
+    /// The fraction of its max HP at which the mob flees through a random exit.
+    #[serde(default)]
+    pub flee_threshold: Option<f64>,
+    /// How many rooms the mob chases a target that left the fight.
+    #[serde(default)]
+    pub pursuit_range: u32,
+    #[serde(default)]
+    pub abilities: Vec<MobAbility>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MobAbility {
+    pub effect: AbilityEffect,
+    /// How long the mob waits before using the ability again, counted from the start of a fight
+    /// for its first use.
+    #[serde(
+        default = "TickDuration::zero",
+        deserialize_with = "TickDuration::deserialize_from_secs"
+    )]
+    pub cooldown: TickDuration,
+    /// A warning shown to the room a few seconds before the ability goes off.
+    #[serde(default)]
+    pub telegraph: Option<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AbilityEffect {
+    /// Hits every player the mob is hostile to in its room.
+    Breath { damage: i32 },
+    /// Puts a status effect on the mob's target.
+    Afflict(StatusApplication),
+    /// Puts a status effect on the mob itself.
+    Empower(StatusApplication),
+    /// Brings in mobs of the template until the summoner has `count` of them.
+    Summon { template: Id<MobTemplate>, count: usize },
+    /// Multiplies the mob's damage once, when its HP drops to the threshold.
+    Enrage { threshold: f64, multiplier: f64 },
+}
+
+impl MobTemplate {
+    /// Whether a mob of this template joins a fight against a mob of the other template.
+    pub fn assists(&self, other: &MobTemplate) -> bool {
+        !self.ignores_fights && self.faction.is_some() && self.faction == other.faction
+    }
+}
+
+impl Mob {
+    pub fn is_stunned(&self, now: Tick) -> bool {
+        self.status_effects.has(StatusKind::Stun, now)
+    }
+
+    pub fn can_use(&self, ability: &MobAbility) -> bool {
+        match ability.effect {
+            AbilityEffect::Enrage { threshold, .. } => {
+                !self.enraged && f64::from(self.hp) <= threshold * f64::from(self.max_hp)
+            }
+            _ => true,
+        }
+    }
+
+    pub fn should_flee(&self) -> bool {
+        self.attack_target.is_some() && self.is_badly_hurt()
+    }
+
+    /// Whether the mob's HP is at or below the share it flees at.
+    pub fn is_badly_hurt(&self) -> bool {
+        self.template
+            .flee_threshold
+            .is_some_and(|threshold| f64::from(self.hp) <= threshold * f64::from(self.max_hp))
+    }
 }
 
 impl Named for MobTemplate {
@@ -34,12 +210,131 @@ impl Named for MobTemplate {
     }
 }
 
+/// A stat that is either fixed, e.g. `30`, or rolled at spawn from a range, e.g. `[25, 35]`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(from = "StatRangeRepr")]
+pub struct StatRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl StatRange {
+    pub fn roll(&self, rng: &mut impl Rng) -> i32 {
+        if self.max > self.min {
+            rng.gen_range(self.min..=self.max)
+        } else {
+            self.min
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StatRangeRepr {
+    Fixed(i32),
+    Range(i32, i32),
+}
+
+impl From<StatRangeRepr> for StatRange {
+    fn from(repr: StatRangeRepr) -> StatRange {
+        match repr {
+            StatRangeRepr::Fixed(value) => StatRange { min: value, max: value },
+            StatRangeRepr::Range(min, max) => StatRange { min, max },
+        }
+    }
+}
+
+/// Builds the mob templates from their YAML definitions. A template with `extends` starts out
+/// with every field of its parent template and overrides the ones it defines itself.
+pub fn resolve_templates(definitions: Vec<Value>) -> Result<IdMap<MobTemplate>, String> {
+    let mut mappings = HashMap::new();
+    for definition in definitions {
+        let Value::Mapping(mapping) = definition else {
+            return Err("Mob template is not a mapping".to_string());
+        };
+        let id = template_id(&mapping, "id")?.ok_or("Mob template without id")?;
+        if mappings.insert(id, mapping).is_some() {
+            return Err(format!("Duplicate mob template {}", id));
+        }
+    }
+
+    let mut resolved = HashMap::new();
+    for id in mappings.keys() {
+        resolve_mapping(*id, &mappings, &mut resolved, &mut vec![])?;
+    }
+    resolved
+        .into_iter()
+        .map(|(id, mapping)| {
+            serde_yaml::from_value(Value::Mapping(mapping))
+                .map(|template| (Id::new(id), template))
+                .map_err(|err| format!("Mob template {}: {}", id, err))
+        })
+        .collect()
+}
+
+fn resolve_mapping(
+    id: u64,
+    mappings: &HashMap<u64, Mapping>,
+    resolved: &mut HashMap<u64, Mapping>,
+    extending: &mut Vec<u64>,
+) -> Result<Mapping, String> {
+    if let Some(mapping) = resolved.get(&id) {
+        return Ok(mapping.clone());
+    }
+    if extending.contains(&id) {
+        return Err(format!(
+            "Mob template {} extends itself through its parents",
+            id
+        ));
+    }
+    let mapping = mappings.get(&id).ok_or(format!("Mob template {} not found", id))?;
+
+    let mut result = match template_id(mapping, "extends")? {
+        Some(parent_id) => {
+            extending.push(id);
+            let parent = resolve_mapping(parent_id, mappings, resolved, extending)?;
+            extending.pop();
+            parent
+        }
+        None => Mapping::new(),
+    };
+    for (key, value) in mapping {
+        if key.as_str() != Some("extends") {
+            result.insert(key.clone(), value.clone());
+        }
+    }
+    resolved.insert(id, result.clone());
+    Ok(result)
+}
+
+fn template_id(mapping: &Mapping, key: &str) -> Result<Option<u64>, String> {
+    match mapping.get(&Value::from(key)) {
+        Some(value) => value.as_u64().map(Some).ok_or(format!("Invalid mob template {}", key)),
+        None => Ok(None),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Mob {
     pub id: Id<Mob>,
     pub room_id: Id<Room>,
+    /// The spawn this mob belongs to, if it was not spawned by hand.
+    pub spawn_origin: Option<SpawnOrigin>,
+    /// The mob that summoned this one. Summoned mobs vanish once it is out of the fight.
+    pub summoner: Option<Id<Mob>>,
     pub template: MobTemplate,
     pub hp: i32,
+    /// Rolled from the template when the mob spawns.
+    pub max_hp: i32,
+    pub damage: i32,
+    /// How many rooms the mob has chased its current target through.
+    pub pursued_rooms: u32,
+    /// When each ability is off cooldown, by its index in the template. Empty outside fights.
+    pub ability_ready_at: HashMap<usize, Tick>,
+    /// A telegraphed ability and when it goes off.
+    pub pending_ability: Option<(usize, Tick)>,
+    pub enraged: bool,
+    pub status_effects: StatusEffects,
     pub attack_offset: TickDuration,
     pub hostile_to: HashSet<Id<Player>>,
     pub attack_target: Option<Id<Player>>,
@@ -52,6 +52,7 @@ impl MobColl {
             let before = mob.clone();
             let result = f(mob);
             let Mob { room_id: after_room_id, hp: after_hp, .. } = *mob;
+            let effects_changed = before.status_effects != mob.status_effects;
 
             if before.room_id != after_room_id {
                 self.remove_from_room_index(*id, before.room_id);
@@ -59,7 +60,7 @@ impl MobColl {
                 self.room_info_changed.insert(before.room_id);
                 self.room_info_changed.insert(after_room_id);
             }
-            if before.hp != after_hp {
+            if before.hp != after_hp || effects_changed {
                 self.room_info_changed.insert(before.room_id);
             }
 
@@ -6,4 +6,27 @@ pub trait Named {
         self.get_name().eq_ignore_ascii_case(str)
             || self.get_aliases().iter().any(|alias| alias.eq_ignore_ascii_case(str))
     }
+
+    /// Matches abbreviations like `drag` or `red drag` for "young red dragon": every word
+    /// of the query has to start one of the words of the name or an alias, in order.
+    fn matches_partial(&self, str: &str) -> bool {
+        std::iter::once(self.get_name())
+            .chain(self.get_aliases())
+            .any(|name| words_start_with(name, str))
+    }
+}
+
+fn words_start_with(name: &str, query: &str) -> bool {
+    let mut name_words = name.split_whitespace();
+    let mut query_words = query.split_whitespace().peekable();
+    if query_words.peek().is_none() {
+        return false;
+    }
+    query_words.all(|query_word| {
+        name_words.any(|name_word| {
+            name_word
+                .get(..query_word.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(query_word))
+        })
+    })
 }
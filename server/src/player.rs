@@ -1,12 +1,62 @@
-use crate::{game_state::Room, id::Id, mob::Mob, tick::TickDuration};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::{
+    account::Role,
+    game_state::Room,
+    id::Id,
+    mob::Mob,
+    status_effect::{StatusEffects, StatusKind},
+    tick::{Tick, TickDuration},
+};
 
 #[derive(Clone, Debug)]
 pub struct Player {
     pub id: Id<Player>,
     pub name: String,
+    pub role: Role,
+    pub password_hash: Option<String>,
     pub room_id: Id<Room>,
     pub hp: i32,
     pub max_hp: i32,
+    pub mana: i32,
+    pub max_mana: i32,
     pub attack_offset: TickDuration,
-    pub attack_target: Option<Id<Mob>>,
+    pub attack_target: Option<AttackTarget>,
+    pub ignored: HashSet<String>,
+    pub recent_chat: VecDeque<(Tick, String)>,
+    pub aliases: BTreeMap<String, String>,
+    pub command_queue: VecDeque<String>,
+    pub command_budget: usize,
+    pub visited_rooms: HashSet<Id<Room>>,
+    pub travel_path: VecDeque<Id<Room>>,
+    pub status_effects: StatusEffects,
+    /// The names of the skills the player has learned.
+    pub skills: BTreeSet<String>,
+    pub skill_ready_at: HashMap<String, Tick>,
+    /// The HP below which the player flees from a fight automatically.
+    pub wimpy: Option<i32>,
+    /// Opted in to fighting other players outside of PvP rooms and zones.
+    pub pvp: bool,
+    /// How many other players this player has killed.
+    pub player_kills: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackTarget {
+    Mob(Id<Mob>),
+    Player(Id<Player>),
+}
+
+impl Player {
+    pub fn is_ignoring(&self, other: &Player) -> bool {
+        self.ignored.contains(&other.name.to_ascii_lowercase())
+    }
+
+    pub fn is_stunned(&self, now: Tick) -> bool {
+        self.status_effects.has(StatusKind::Stun, now)
+    }
+
+    pub fn has_visited(&self, room_id: Id<Room>) -> bool {
+        self.visited_rooms.contains(&room_id)
+    }
 }
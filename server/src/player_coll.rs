@@ -26,8 +26,8 @@ impl PlayerColl {
         &self.by_id
     }
 
-    pub fn by_room_id(&self) -> &HashMap<Id<Room>, Vec<Id<Player>>> {
-        &self.by_room_id
+    pub fn by_name(&self, name: &str) -> Option<&Player> {
+        self.by_id.values().find(|player| player.name.eq_ignore_ascii_case(name))
     }
 
     pub fn room_info_changed(&self) -> &HashSet<Id<Room>> {
@@ -69,8 +69,14 @@ impl PlayerColl {
             let before = mob.clone();
             let result = f(mob);
             let Player {
-                room_id: after_room_id, hp: after_hp, max_hp: after_max_hp, ..
+                room_id: after_room_id,
+                hp: after_hp,
+                max_hp: after_max_hp,
+                mana: after_mana,
+                max_mana: after_max_mana,
+                ..
             } = *mob;
+            let effects_changed = before.status_effects != mob.status_effects;
 
             if before.room_id != after_room_id {
                 self.remove_from_room_index(*id, before.room_id);
@@ -78,7 +84,10 @@ impl PlayerColl {
                 self.room_info_changed.insert(before.room_id);
                 self.room_info_changed.insert(after_room_id);
             }
-            if (before.hp, before.max_hp) != (after_hp, after_max_hp) {
+            if (before.hp, before.max_hp, before.mana, before.max_mana)
+                != (after_hp, after_max_hp, after_mana, after_max_mana)
+                || effects_changed
+            {
                 self.room_info_changed.insert(before.room_id);
             }
 
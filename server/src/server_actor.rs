@@ -1,30 +1,44 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use futures_util::future;
 use log::{debug, warn};
-use rand::thread_rng;
 use serde::Serialize;
 use tokio::{sync::mpsc, time};
 
 use crate::{
+    account::Role,
+    auth::Authenticator,
+    character::SavedCharacter,
     event_writer::EventWriter,
-    game_combat, game_logic,
+    file_io::{self, FileIo, FileRequest},
+    game_combat, game_logic, game_map, game_queue,
+    game_room::exit_target,
     game_state::{GameState, LoadedGameState, Room},
     id::Id,
     line::Line,
     player::Player,
+    status_effect::{StatusEffects, StatusKind},
     tick,
 };
 
+static AUDIT_LOG_PATH: &str = "audit.log";
+static MAP_INFO_RADIUS: i32 = 2;
+
 #[derive(Debug)]
 pub enum Message {
     PlayerConnected {
         player_id: Id<Player>,
         player_name: String,
+        saved: SavedCharacter,
         connection: mpsc::Sender<PlayerEvent>,
     },
     PlayerDisconnected {
         player_id: Id<Player>,
+        player_name: String,
     },
     PlayerCommand {
         player_id: Id<Player>,
@@ -38,6 +52,7 @@ pub enum Message {
 pub struct PlayerEvent {
     lines: Vec<Line>,
     room_info: Option<RoomInfo>,
+    map_info: Option<MapInfo>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -55,12 +70,62 @@ pub struct EntityInfo {
     name: String,
     hp: i32,
     max_hp: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mana: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_mana: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<StatusKind>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapInfo {
+    room_id: String,
+    name: String,
+    exits: Vec<MapExit>,
+    neighbours: Vec<MapRoom>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapExit {
+    direction: String,
+    to_room_id: String,
+    open: bool,
+}
+
+/// A room the player has explored near the current one, positioned relative to it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapRoom {
+    id: String,
+    name: String,
+    x: i32,
+    y: i32,
+    exits: Vec<MapExit>,
+}
+
+/// What a player's map info is computed from. Visited rooms are only ever added, so their count
+/// tells whether they changed.
+#[derive(Debug, PartialEq, Eq)]
+struct MapInfoKey {
+    room_id: Id<Room>,
+    visited_rooms: usize,
+    map_version: u64,
+}
+
+struct SentMapInfo {
+    key: MapInfoKey,
+    map_info: MapInfo,
 }
 
 pub async fn run(
     mut messages: mpsc::Receiver<Message>,
     self_sender: mpsc::Sender<Message>,
     loaded_game_state: LoadedGameState,
+    authenticator: Arc<Authenticator>,
+    file_io: FileIo,
 ) {
     use Message::*;
 
@@ -73,34 +138,61 @@ pub async fn run(
     });
 
     let mut connections: HashMap<Id<Player>, _> = HashMap::new();
+    let mut sent_map_infos: HashMap<Id<Player>, SentMapInfo> = HashMap::new();
     let mut game_state = GameState::new(loaded_game_state);
-    let mut event_writer = EventWriter { lines: HashMap::new() };
+    let mut event_writer = EventWriter::new();
 
     game_logic::initialize(&mut game_state);
 
     debug!("Server loop starting");
     while let Some(message) = messages.recv().await {
+        let mut released_name = None;
         match message {
-            PlayerConnected { player_id, player_name, connection } => {
+            PlayerConnected { player_id, player_name, saved, connection } => {
                 connections.insert(player_id, connection);
                 let player = Player {
                     id: player_id,
                     name: player_name,
+                    role: Role::Player,
+                    password_hash: saved.password_hash,
                     room_id: Id::new(0),
                     hp: 100,
                     max_hp: 100,
-                    attack_offset: game_combat::PLAYER_ATTACK_FREQ.random_offset(&mut thread_rng()),
+                    mana: 100,
+                    max_mana: 100,
+                    attack_offset: game_combat::PLAYER_ATTACK_FREQ
+                        .random_offset(&mut game_state.rng),
                     attack_target: None,
+                    ignored: saved.ignored.into_iter().collect(),
+                    recent_chat: VecDeque::new(),
+                    aliases: saved.aliases,
+                    command_queue: VecDeque::new(),
+                    command_budget: 0,
+                    visited_rooms: saved.visited_rooms.into_iter().collect(),
+                    travel_path: VecDeque::new(),
+                    status_effects: StatusEffects::default(),
+                    skills: game_state
+                        .skills
+                        .values()
+                        .filter(|skill| skill.innate)
+                        .map(|skill| skill.name.clone())
+                        .chain(saved.skills)
+                        .collect(),
+                    skill_ready_at: HashMap::new(),
+                    wimpy: saved.wimpy,
+                    pvp: saved.pvp,
+                    player_kills: saved.player_kills,
                 };
                 game_logic::on_player_connect(player, &mut event_writer, &mut game_state);
             }
-            PlayerDisconnected { player_id } => {
+            PlayerDisconnected { player_id, player_name } => {
                 connections.remove(&player_id);
                 game_logic::on_player_disconnect(player_id, &mut event_writer, &mut game_state);
+                released_name = Some(player_name);
             }
             PlayerCommand { player_id, command } => {
                 if let Err(err) =
-                    game_logic::on_command(player_id, &command, &mut event_writer, &mut game_state)
+                    game_queue::on_input(player_id, &command, &mut event_writer, &mut game_state)
                 {
                     warn!("Player command: {}", err);
                 }
@@ -109,13 +201,43 @@ pub async fn run(
                 game_logic::on_tick(&mut event_writer, &mut game_state);
             }
         }
-        send_player_events(&mut game_state, &connections, &mut event_writer).await;
+        send_player_events(
+            &mut game_state,
+            &connections,
+            &mut sent_map_infos,
+            &mut event_writer,
+        )
+        .await;
+        for player_id in event_writer.disconnects.drain() {
+            connections.remove(&player_id);
+        }
+        write_audit_log(&mut event_writer.audit_entries, &file_io);
+        for request in event_writer.file_requests.drain(..) {
+            file_io::send(&file_io, request);
+        }
+        // Only after the player's save has been sent, so logging in again loads the new save.
+        if let Some(player_name) = released_name {
+            authenticator.release_name(&player_name);
+        }
     }
 }
 
+fn write_audit_log(entries: &mut Vec<String>, file_io: &FileIo) {
+    if entries.is_empty() {
+        return;
+    }
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let contents = entries.drain(..).map(|entry| format!("{} {}\n", secs, entry)).collect();
+    file_io::send(
+        file_io,
+        FileRequest::Append { path: AUDIT_LOG_PATH.into(), contents },
+    );
+}
+
 async fn send_player_events(
     state: &mut GameState,
     connections: &HashMap<Id<Player>, mpsc::Sender<PlayerEvent>>,
+    sent_map_infos: &mut HashMap<Id<Player>, SentMapInfo>,
     event_writer: &mut EventWriter,
 ) {
     let room_infos = state
@@ -126,13 +248,40 @@ async fn send_player_events(
         .flat_map(|room_id| collect_room_info(*room_id, state))
         .collect::<HashMap<_, _>>();
 
-    let player_ids = room_infos.keys().chain(event_writer.lines.keys()).collect::<HashSet<_>>();
+    sent_map_infos.retain(|player_id, _| connections.contains_key(player_id));
+    let map_infos = state
+        .players
+        .by_id()
+        .values()
+        .filter_map(|player| {
+            let key = MapInfoKey {
+                room_id: player.room_id,
+                visited_rooms: player.visited_rooms.len(),
+                map_version: state.map_version,
+            };
+            let sent = sent_map_infos.get(&player.id);
+            if sent.is_some_and(|sent| sent.key == key) {
+                return None;
+            }
+            let map_info = collect_map_info(player, state)?;
+            let changed = sent.is_none_or(|sent| sent.map_info != map_info);
+            sent_map_infos.insert(player.id, SentMapInfo { key, map_info: map_info.clone() });
+            changed.then_some((player.id, map_info))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let player_ids = room_infos
+        .keys()
+        .chain(map_infos.keys())
+        .chain(event_writer.lines.keys())
+        .collect::<HashSet<_>>();
 
     future::try_join_all(player_ids.into_iter().filter_map(|player_id| {
         if let Some(connection) = connections.get(player_id) {
             let lines = event_writer.lines.get(player_id).cloned().unwrap_or_default();
             let room_info = room_infos.get(player_id).cloned();
-            let event = PlayerEvent { lines, room_info };
+            let map_info = map_infos.get(player_id).cloned();
+            let event = PlayerEvent { lines, room_info, map_info };
             Some(connection.send(event))
         } else {
             None
@@ -146,6 +295,45 @@ async fn send_player_events(
     state.mobs.clear_room_info_changed();
 }
 
+fn collect_map_info(player: &Player, state: &GameState) -> Option<MapInfo> {
+    let room = state.rooms.get(&player.room_id)?;
+    let map_exits = |room: &Room| {
+        room.exits
+            .iter()
+            .map(|(direction, exit)| {
+                let (to_room_id, open) = exit_target(exit, room.id, state);
+                MapExit {
+                    direction: direction.clone(),
+                    to_room_id: to_room_id.value.to_string(),
+                    open,
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut neighbours = game_map::layout(room.id, MAP_INFO_RADIUS, state)
+        .into_iter()
+        .filter(|(room_id, _)| *room_id != room.id && player.has_visited(*room_id))
+        .filter_map(|(room_id, (x, y))| {
+            state.rooms.get(&room_id).map(|neighbour| MapRoom {
+                id: room_id.value.to_string(),
+                name: neighbour.name.clone(),
+                x,
+                y,
+                exits: map_exits(neighbour),
+            })
+        })
+        .collect::<Vec<_>>();
+    neighbours.sort_by_key(|neighbour| (neighbour.y, neighbour.x));
+
+    Some(MapInfo {
+        room_id: room.id.value.to_string(),
+        name: room.name.clone(),
+        exits: map_exits(room),
+        neighbours,
+    })
+}
+
 fn collect_room_info(room_id: Id<Room>, state: &GameState) -> Vec<(Id<Player>, RoomInfo)> {
     let (player_ids, players): (Vec<_>, Vec<_>) = state
         .players
@@ -160,6 +348,9 @@ fn collect_room_info(room_id: Id<Room>, state: &GameState) -> Vec<(Id<Player>, R
                         name: p.name.clone(),
                         hp: p.hp,
                         max_hp: p.max_hp,
+                        mana: Some(p.mana),
+                        max_mana: Some(p.max_mana),
+                        effects: active_effects(&p.status_effects, state),
                     },
                 ))
             } else {
@@ -177,7 +368,10 @@ fn collect_room_info(room_id: Id<Room>, state: &GameState) -> Vec<(Id<Player>, R
                     id: m.id.value.to_string(),
                     name: m.template.name.clone(),
                     hp: m.hp,
-                    max_hp: m.template.max_hp,
+                    max_hp: m.max_hp,
+                    mana: None,
+                    max_mana: None,
+                    effects: active_effects(&m.status_effects, state),
                 })
             } else {
                 None
@@ -198,3 +392,11 @@ fn collect_room_info(room_id: Id<Room>, state: &GameState) -> Vec<(Id<Player>, R
         })
         .collect()
 }
+
+fn active_effects(effects: &StatusEffects, state: &GameState) -> Vec<StatusKind> {
+    effects
+        .iter()
+        .filter(|(_, active)| state.ticks < active.until)
+        .map(|(kind, _)| kind)
+        .collect()
+}
@@ -1,14 +1,26 @@
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
 use futures_util::{SinkExt, StreamExt};
-use log::debug;
+use log::{debug, warn};
 use serde::Deserialize;
 use tokio::sync::mpsc;
 use warp::ws::{Message, WebSocket};
 
-use crate::{id::Id, server_actor};
+use crate::{
+    auth::Authenticator,
+    character::{self, SavedCharacter},
+    file_io::FileIo,
+    id::Id,
+    server_actor,
+};
 
 static NEXT_PLAYER_ID: AtomicU64 = AtomicU64::new(0);
+/// The close code sent with the reason when a login is refused, from the range free for
+/// applications.
+static LOGIN_REFUSED_CODE: u16 = 4001;
 
 #[derive(Deserialize, Debug)]
 pub struct ConnectQuery {
@@ -19,11 +31,29 @@ pub async fn handle_connection(
     ws: WebSocket,
     connect_query: ConnectQuery,
     actor_sender: mpsc::Sender<server_actor::Message>,
+    authenticator: Arc<Authenticator>,
+    file_io: FileIo,
 ) {
     debug!("New connection");
-    let player_id = Id::new(NEXT_PLAYER_ID.fetch_add(1, Ordering::SeqCst));
     let (mut sink, mut stream) = ws.split();
 
+    // The client sends the password as its first message.
+    let password = match stream.next().await {
+        Some(Ok(message)) => message.to_str().unwrap_or_default().to_string(),
+        _ => return,
+    };
+    let player_name = connect_query.name;
+    let saved = match log_in(&player_name, password, &authenticator, &file_io).await {
+        Ok(saved) => saved,
+        Err(reason) => {
+            debug!("Login as {} refused: {}", player_name, reason);
+            let _ = sink.send(Message::close_with(LOGIN_REFUSED_CODE, reason)).await;
+            return;
+        }
+    };
+
+    let player_id = Id::new(NEXT_PLAYER_ID.fetch_add(1, Ordering::SeqCst));
+
     let (event_sender, mut event_receiver) = mpsc::channel::<server_actor::PlayerEvent>(64);
     tokio::spawn(async move {
         while let Some(event) = event_receiver.recv().await {
@@ -37,7 +67,8 @@ pub async fn handle_connection(
     actor_sender
         .send(server_actor::Message::PlayerConnected {
             player_id,
-            player_name: connect_query.name,
+            player_name: player_name.clone(),
+            saved,
             connection: event_sender,
         })
         .await
@@ -54,8 +85,34 @@ pub async fn handle_connection(
         }
     }
     actor_sender
-        .send(server_actor::Message::PlayerDisconnected { player_id })
+        .send(server_actor::Message::PlayerDisconnected { player_id, player_name })
         .await
         .unwrap();
     debug!("Receiver closed");
 }
+
+/// Checks the name and password, and claims the name until the actor releases it on disconnect.
+async fn log_in(
+    player_name: &str,
+    password: String,
+    authenticator: &Authenticator,
+    file_io: &FileIo,
+) -> Result<SavedCharacter, &'static str> {
+    if !character::is_valid_name(player_name) {
+        return Err("Names can only have up to 20 letters, digits, - and _.");
+    }
+    if !authenticator.claim_name(player_name) {
+        return Err("That character is already playing.");
+    }
+    let result = match SavedCharacter::load(player_name, file_io).await {
+        Ok(saved) => authenticator.authenticate(player_name, password, saved).await,
+        Err(err) => {
+            warn!("{}", err);
+            Err("Your character could not be loaded.")
+        }
+    };
+    if result.is_err() {
+        authenticator.release_name(player_name);
+    }
+    result
+}
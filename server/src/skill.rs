@@ -0,0 +1,35 @@
+use serde::Deserialize;
+
+use crate::{damage::DamageType, status_effect::StatusApplication, tick::TickDuration};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Skill {
+    pub name: String,
+    pub description: String,
+    pub mana_cost: i32,
+    #[serde(deserialize_with = "TickDuration::deserialize_from_secs")]
+    pub cooldown: TickDuration,
+    pub target: SkillTarget,
+    #[serde(default)]
+    pub damage: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub heal: i32,
+    /// A status effect put on the target.
+    #[serde(default)]
+    pub status: Option<StatusApplication>,
+    /// Known by every character from the start, instead of being learned in the world.
+    #[serde(default)]
+    pub innate: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SkillTarget {
+    /// A mob in the room, the one the player is fighting if none is given.
+    Enemy,
+    /// A player in the room, the player using the skill if none is given.
+    Ally,
+}
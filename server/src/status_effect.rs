@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tick::{Tick, TickDuration};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum StatusKind {
+    /// Loses HP every second.
+    Poison,
+    /// Recovers HP every second.
+    Regeneration,
+    /// Cannot act.
+    Stun,
+    /// Attacks twice as often.
+    Haste,
+    /// Deals extra damage.
+    Blessing,
+}
+
+impl StatusKind {
+    /// How an affected entity is described, e.g. "The skeleton is poisoned."
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "poisoned",
+            StatusKind::Regeneration => "regenerating",
+            StatusKind::Stun => "stunned",
+            StatusKind::Haste => "hasted",
+            StatusKind::Blessing => "blessed",
+        }
+    }
+
+    pub fn wear_off_message(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "The poison wears off.",
+            StatusKind::Regeneration => "You stop regenerating.",
+            StatusKind::Stun => "You are no longer stunned.",
+            StatusKind::Haste => "You slow down to your usual pace.",
+            StatusKind::Blessing => "The blessing fades.",
+        }
+    }
+}
+
+/// A status effect as given by a skill, a mob ability or a room command.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusApplication {
+    pub status: StatusKind,
+    pub secs: f32,
+    /// HP per second for poison and regeneration, extra damage for blessing.
+    #[serde(default)]
+    pub potency: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveStatus {
+    pub until: Tick,
+    pub potency: i32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatusEffects(BTreeMap<StatusKind, ActiveStatus>);
+
+impl StatusEffects {
+    /// Adds the effect, or refreshes it with the longer duration and the stronger potency.
+    pub fn apply(&mut self, application: &StatusApplication, now: Tick) {
+        let until = now + TickDuration::from_secs(application.secs);
+        let active = self
+            .0
+            .entry(application.status)
+            .or_insert(ActiveStatus { until, potency: application.potency });
+        active.until = active.until.max(until);
+        active.potency = active.potency.max(application.potency);
+    }
+
+    pub fn has(&self, kind: StatusKind, now: Tick) -> bool {
+        self.0.get(&kind).is_some_and(|active| now < active.until)
+    }
+
+    pub fn potency(&self, kind: StatusKind, now: Tick) -> i32 {
+        self.0
+            .get(&kind)
+            .filter(|active| now < active.until)
+            .map_or(0, |active| active.potency)
+    }
+
+    /// Removes the effects that have run out and returns their kinds.
+    pub fn expire(&mut self, now: Tick) -> Vec<StatusKind> {
+        let expired = self
+            .0
+            .iter()
+            .filter(|(_, active)| active.until <= now)
+            .map(|(kind, _)| *kind)
+            .collect::<Vec<_>>();
+        for kind in &expired {
+            self.0.remove(kind);
+        }
+        expired
+    }
+
+    pub fn has_expired(&self, now: Tick) -> bool {
+        self.0.values().any(|active| active.until <= now)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (StatusKind, &ActiveStatus)> {
+        self.0.iter().map(|(kind, active)| (*kind, active))
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::{
+    account::Role,
+    config::Config,
+    game_chat::ChatConfig,
+    game_state::{GameState, LoadedGameState, Room},
+    id::Id,
+    mob,
+    player::Player,
+    status_effect::StatusEffects,
+    tick::TickDuration,
+    zone::Zone,
+};
+
+/// A game with a single zone, the given rooms and mob templates as they would be written in
+/// the data files, and a fixed RNG seed.
+pub fn game_state(rooms_yaml: &str, mob_templates_yaml: &str) -> GameState {
+    let zones: Vec<Zone> =
+        serde_yaml::from_str("[{ id: 0, name: Zone, respawnRoomId: 0 }]").unwrap();
+    let rooms: Vec<Room> = serde_yaml::from_str(rooms_yaml).unwrap();
+    let chat_config: ChatConfig =
+        serde_yaml::from_str("flood: { maxMessages: 5, windowSecs: 10, maxRepeats: 2 }").unwrap();
+    GameState::new(LoadedGameState {
+        zones: zones.into_iter().map(|zone| (zone.id, zone)).collect(),
+        rooms: rooms.into_iter().map(|room| (room.id, room)).collect(),
+        mob_templates: mob::resolve_templates(serde_yaml::from_str(mob_templates_yaml).unwrap())
+            .unwrap(),
+        skills: Vec::new(),
+        chat_config,
+        accounts: Vec::new(),
+        config: Config {
+            commands_per_tick: 1,
+            max_queued_commands: 40,
+            ambient_message_chance: 0.0,
+            game_hour_secs: 150.0,
+            rng_seed: Some(0),
+        },
+    })
+}
+
+/// Adds a fresh player to the room and returns their id.
+pub fn add_player(name: &str, room_id: Id<Room>, state: &mut GameState) -> Id<Player> {
+    let id = Id::new(state.players.by_id().len() as u64);
+    state.players.insert(Player {
+        id,
+        name: name.to_string(),
+        role: Role::Player,
+        password_hash: None,
+        room_id,
+        hp: 100,
+        max_hp: 100,
+        mana: 100,
+        max_mana: 100,
+        attack_offset: TickDuration::zero(),
+        attack_target: None,
+        ignored: HashSet::new(),
+        recent_chat: VecDeque::new(),
+        aliases: BTreeMap::new(),
+        command_queue: VecDeque::new(),
+        command_budget: 0,
+        visited_rooms: HashSet::new(),
+        travel_path: VecDeque::new(),
+        status_effects: StatusEffects::default(),
+        skills: BTreeSet::new(),
+        skill_ready_at: HashMap::new(),
+        wimpy: None,
+        pvp: false,
+        player_kills: 0,
+    });
+    id
+}
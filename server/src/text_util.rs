@@ -1,15 +1,23 @@
 use crate::line::{span, LineSpan};
 
-pub fn and_spans(mut words: Vec<LineSpan>) -> Vec<LineSpan> {
+pub fn and_spans(words: Vec<LineSpan>) -> Vec<LineSpan> {
+    join_spans(words, " and ")
+}
+
+pub fn or_spans(words: Vec<LineSpan>) -> Vec<LineSpan> {
+    join_spans(words, " or ")
+}
+
+fn join_spans(mut words: Vec<LineSpan>, last_separator: &str) -> Vec<LineSpan> {
     match words.len() {
         0 => words,
         1 => words,
         2 => {
-            words.insert(1, span(" and "));
+            words.insert(1, span(last_separator));
             words
         }
         len => {
-            words.insert(len - 1, span(" and "));
+            words.insert(len - 1, span(last_separator));
             for i in (1..len - 1).rev() {
                 words.insert(i, span(", "));
             }
@@ -51,3 +59,37 @@ pub fn plural(len: usize, str: &str) -> String {
         str.to_string()
     }
 }
+
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b_chars.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b_chars.len()]
+}
+
+/// The longest duration `parse_duration_secs` accepts: 30 days.
+static MAX_DURATION_SECS: f32 = 30.0 * 24.0 * 3600.0;
+
+/// Parses durations like `90`, `30s`, `10m` or `2h` into seconds, defaulting to minutes. Durations
+/// longer than 30 days are clamped to 30 days.
+pub fn parse_duration_secs(str: &str) -> Option<f32> {
+    let (number, multiplier) = match str.chars().last()? {
+        's' => (&str[..str.len() - 1], 1.0),
+        'm' => (&str[..str.len() - 1], 60.0),
+        'h' => (&str[..str.len() - 1], 3600.0),
+        _ => (str, 60.0),
+    };
+    number
+        .parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite() && *n > 0.0)
+        .map(|n| (n * multiplier).min(MAX_DURATION_SECS))
+}
@@ -22,12 +22,16 @@ impl Tick {
     }
 
     pub fn is_on_division(&self, divide: TickDuration, offset: TickDuration) -> bool {
-        self.0 % divide.0 == offset.0
+        self.0 % divide.0 == offset.0 % divide.0
     }
 
     pub fn increase(&self) -> Tick {
         Tick(self.0 + 1)
     }
+
+    pub fn as_secs(&self) -> f32 {
+        self.0 as f32 * TICK_INTERVAL.as_secs_f32()
+    }
 }
 
 impl TickDuration {
@@ -35,6 +39,10 @@ impl TickDuration {
         TickDuration(0)
     }
 
+    pub fn one() -> TickDuration {
+        TickDuration(1)
+    }
+
     pub fn from_secs(secs: f32) -> TickDuration {
         TickDuration((secs / TICK_INTERVAL.as_secs_f32()) as i64)
     }
@@ -46,6 +54,17 @@ impl TickDuration {
         f32::deserialize(deserializer).map(TickDuration::from_secs)
     }
 
+    pub fn deserialize_option_from_secs<'de, D>(deserializer: D) -> Result<Option<Self>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<f32>::deserialize(deserializer).map(|secs| secs.map(TickDuration::from_secs))
+    }
+
+    pub fn as_secs(&self) -> f32 {
+        self.0 as f32 * TICK_INTERVAL.as_secs_f32()
+    }
+
     pub fn random_offset(&self, rng: &mut impl Rng) -> TickDuration {
         TickDuration(rng.gen_range(0..self.0))
     }
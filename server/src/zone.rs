@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{game_state::Room, id::Id, tick::TickDuration};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Zone {
+    pub id: Id<Zone>,
+    pub name: String,
+    #[serde(default)]
+    pub level_range: Option<(u32, u32)>,
+    /// Where players who die in this zone come back to life.
+    pub respawn_room_id: Id<Room>,
+    #[serde(default)]
+    pub ambient_messages: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "TickDuration::deserialize_option_from_secs"
+    )]
+    pub reset_interval: Option<TickDuration>,
+    #[serde(default)]
+    pub reset_mode: ResetMode,
+    #[serde(default)]
+    pub reset_message: Option<String>,
+    #[serde(default)]
+    pub flags: HashSet<ZoneFlag>,
+}
+
+impl Zone {
+    pub fn has_flag(&self, flag: ZoneFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResetMode {
+    #[default]
+    Always,
+    /// The reset waits until nobody is in the zone.
+    WhenEmpty,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ZoneFlag {
+    /// Nobody can start a fight here.
+    Safe,
+    /// `who` does not tell who is in this zone.
+    Hidden,
+    /// Players can fight each other here without opting in.
+    Pvp,
+}